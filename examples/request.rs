@@ -1,6 +1,6 @@
 use anyhow::Result;
 #[allow(unused_imports)]
-use ftx_rs::{model, request, FtxClient};
+use ftx_rs::{model, request, Credentials, FtxClient};
 
 async fn req<Q: request::Request + std::fmt::Debug>(c: &FtxClient, req: Q) {
     let s = format!("{:#?}", req);
@@ -15,12 +15,11 @@ async fn req<Q: request::Request + std::fmt::Debug>(c: &FtxClient, req: Q) {
 async fn main() -> Result<()> {
     env_logger::init();
 
-    // Your private API key, called `API Secret` in the FTX web interface
-    let private_key: String = std::env::var("FTX_PRIVATE").unwrap();
-    // Your public API key, called `API Key` in the FTX web interface
-    let public_key: String = std::env::var("FTX_PUBLIC").unwrap();
-
-    let c = FtxClient::with_auth(&public_key, &private_key, None)?;
+    // Your public API key, called `API Key` in the FTX web interface, is read from `FTX_PUBLIC`
+    // and your private API key, called `API Secret`, from `FTX_PRIVATE`
+    let c = FtxClient::builder()
+        .credentials(Credentials::from_env()?)
+        .build()?;
 
     req(&c, request::Subaccounts).await;
 
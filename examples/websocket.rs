@@ -1,15 +1,14 @@
 use anyhow::Result;
-use ftx_rs::{model, FtxClient};
+use ftx_rs::{model, Credentials, FtxClient};
 use futures::{SinkExt, StreamExt};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init();
 
-    let private_key: String = std::env::var("FTX_PRIVATE").unwrap();
-    let public_key: String = std::env::var("FTX_PUBLIC").unwrap();
-
-    let c = FtxClient::with_auth(&public_key, &private_key, None)?;
+    let c = FtxClient::builder()
+        .credentials(Credentials::from_env()?)
+        .build()?;
     let mut ws = c.websocket().await?;
 
     c.send_ws_auth_msg(&mut ws).await?;
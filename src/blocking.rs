@@ -0,0 +1,124 @@
+use futures::{Stream, StreamExt};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{pin::Pin, sync::Arc};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    client::request::{Paginated, Request},
+    ApiResponse, FtxClient, FtxError, Result,
+};
+
+/// Synchronous wrapper of an [`FtxClient`], for scripts and tools without an async runtime.
+///
+/// Requests are driven by a private single threaded tokio runtime, so methods
+/// block the calling thread and must not be called from within an async context.
+///
+/// ```no_run
+/// # fn main() -> ftx_rs::Result<()> {
+/// use ftx_rs::{request, FtxBlockingClient, FtxClient};
+///
+/// let client = FtxBlockingClient::new(FtxClient::new())?;
+/// let markets = client.request(request::Markets)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FtxBlockingClient {
+    client: FtxClient,
+    runtime: Arc<Runtime>,
+}
+
+impl FtxBlockingClient {
+    /// Wrap `client`, configured with [`FtxClient::builder`] like for async use.
+    pub fn new(client: FtxClient) -> Result<Self> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| FtxError::Config(format!("failed to start runtime: {}", e)))?;
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The async client requests are sent with.
+    pub fn client(&self) -> &FtxClient {
+        &self.client
+    }
+
+    /// See [`FtxClient::request`].
+    pub fn request<Q: Request>(&self, request: Q) -> Result<Q::Response> {
+        self.runtime.block_on(self.client.request(request))
+    }
+
+    /// See [`FtxClient::request_with_meta`].
+    pub fn request_with_meta<Q: Request>(&self, request: Q) -> Result<ApiResponse<Q::Response>> {
+        self.runtime
+            .block_on(self.client.request_with_meta(request))
+    }
+
+    /// See [`FtxClient::request_as`].
+    pub fn request_as<Q: Request>(
+        &self,
+        subaccount: Option<&str>,
+        request: Q,
+    ) -> Result<Q::Response> {
+        self.runtime
+            .block_on(self.client.request_as(subaccount, request))
+    }
+
+    /// See [`FtxClient::raw_request`].
+    pub fn raw_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+        signed: bool,
+    ) -> Result<T> {
+        self.runtime
+            .block_on(self.client.raw_request(method, path, query, body, signed))
+    }
+
+    /// See [`FtxClient::batch`].
+    pub fn batch<Q: Request>(
+        &self,
+        requests: impl IntoIterator<Item = Q>,
+        concurrency: usize,
+    ) -> Vec<Result<Q::Response>> {
+        self.runtime
+            .block_on(self.client.batch(requests, concurrency))
+    }
+
+    /// See [`FtxClient::paginate`], pages are fetched as the iterator advances.
+    pub fn paginate<'a, Q>(&'a self, request: Q) -> Paginate<'a, <Q as Paginated>::Item>
+    where
+        Q: Paginated<Response = Vec<<Q as Paginated>::Item>> + 'a,
+    {
+        Paginate {
+            stream: Box::pin(self.client.paginate(request)),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// See [`FtxClient::sync_clock`].
+    pub fn sync_clock(&self) -> Result<chrono::Duration> {
+        self.runtime.block_on(self.client.sync_clock())
+    }
+}
+
+/// Iterator over the items of a paginated request, see [`FtxBlockingClient::paginate`].
+pub struct Paginate<'a, T> {
+    stream: Pin<Box<dyn Stream<Item = Result<T>> + 'a>>,
+    runtime: &'a Runtime,
+}
+
+impl<T> Iterator for Paginate<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
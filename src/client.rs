@@ -1,37 +1,88 @@
-use anyhow::{anyhow, Context, Result};
-use chrono::Utc;
-use hmac::{Hmac, Mac, NewMac};
 use log::debug;
-use reqwest::{Client, Method, Response};
+use reqwest::{header::HeaderMap, Client, Method, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize};
-use serde_json::from_str;
-use sha2::Sha256;
+use serde_json::{from_str, Value};
+
+use crate::error::{FtxError, Result};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use url::Url;
 
+mod accounts;
+mod batch;
+mod builder;
+mod cassette;
+mod clock;
+mod credentials;
+mod interceptor;
+mod metrics;
+mod net;
+mod paginate;
+mod rate_limit;
 pub mod request;
+mod retry;
+mod signer;
+mod trace;
 mod util;
+#[cfg(feature = "websocket")]
 mod websocket;
 
-use request::Request;
-use util::{HeaderBuilder, ToUrlQuery};
-
-const API_URL: &str = "https://ftx.com/api";
+pub use accounts::{FtxAccounts, PerAccount};
+pub use builder::{Deployment, FtxClientBuilder};
+use cassette::Cassette;
+use clock::Clock;
+pub use credentials::{Credentials, Secret};
+use interceptor::Interceptors;
+pub use interceptor::{Interceptor, RequestInfo, ResponseInfo};
+pub use metrics::{EndpointMetrics, LatencyHistogram, Metrics, MetricsSnapshot};
+use net::NetworkConfig;
+pub use rate_limit::{Quota, RateLimiter};
+use request::{Request, RequestClass};
+pub use retry::RetryPolicy;
+use signer::SharedSigner;
+pub use signer::{AsyncSigner, HmacSigner, Signer};
+use util::{encode_uri_component, HeaderBuilder, ToUrlQuery};
+#[cfg(feature = "websocket")]
+pub use websocket::FtxWebsocket;
 
 #[derive(Debug, Clone)]
 struct Auth {
     public_key: String,
-    private_key: String,
+    signer: SharedSigner,
     subaccount: Option<String>,
 }
 
-impl Auth {
-    /// Compute hmacsha256 hash with FTX private key.
-    /// This is used to sign messages sent to the server.
-    fn sign(&self, prehash: &str) -> Result<String> {
-        let mut mac = Hmac::<Sha256>::new_from_slice(self.private_key.as_bytes())
-            .with_context(|| format!("failed to use FTX private key as a HMAC-SHA256 key"))?;
-        mac.update(prehash.as_bytes());
-        Ok(hex::encode(mac.finalize().into_bytes()))
+/// Endpoints, identification and hooks used by a client, shared between its clones.
+#[derive(Debug, Clone)]
+struct Config {
+    rest_url: String,
+    ws_url: String,
+    user_agent: String,
+    interceptors: Interceptors,
+    cassette: Option<Arc<Cassette>>,
+    /// Proxy, local address and timeouts for websocket connections
+    #[cfg(feature = "websocket")]
+    network: NetworkConfig,
+    /// Counts messages received by websockets, requests are counted by its interceptor
+    #[cfg(feature = "websocket")]
+    metrics: Option<Metrics>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rest_url: Deployment::Ftx.rest_url().into(),
+            ws_url: Deployment::Ftx.websocket_url().into(),
+            user_agent: builder::DEFAULT_USER_AGENT.into(),
+            interceptors: Default::default(),
+            cassette: None,
+            #[cfg(feature = "websocket")]
+            network: Default::default(),
+            #[cfg(feature = "websocket")]
+            metrics: None,
+        }
     }
 }
 
@@ -39,6 +90,32 @@ impl Auth {
 pub struct FtxClient {
     client: Client,
     auth: Option<Auth>,
+    config: Arc<Config>,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: RetryPolicy,
+    clock: Arc<Clock>,
+}
+
+/// Request ready to be sent, kept so that it can be signed again when retried.
+#[derive(Debug)]
+struct PreparedRequest {
+    method: Method,
+    url: Url,
+    /// Path and query as used in the signature
+    path: String,
+    body: Option<String>,
+    signed: bool,
+    class: RequestClass,
+    retryable: bool,
+}
+
+#[derive(Debug)]
+struct RawResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: String,
+    /// Time between sending the request and receiving the whole body
+    latency: Duration,
 }
 
 #[derive(Deserialize, Debug)]
@@ -49,46 +126,93 @@ struct ResponseSchema<T> {
     has_more_data: Option<bool>,
 }
 
+/// Result of a request together with details of the http response carrying it,
+/// returned by [`FtxClient::request_with_meta`].
+#[derive(Debug, Clone)]
+pub struct ApiResponse<T> {
+    pub result: T,
+    /// Set by endpoints that return results in pages
+    pub has_more_data: Option<bool>,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Round trip time of the last attempt, without rate limiting or retry delays
+    pub latency: Duration,
+    pub raw_body: String,
+}
+
+/// Body FTX sends with `"success": false`, both with error and success http statuses.
+#[derive(Deserialize, Debug)]
+struct ErrorSchema {
+    success: bool,
+    error: Option<String>,
+}
+
 impl FtxClient {
     pub fn new() -> Self {
         Default::default()
     }
 
+    /// Start configuring a client, see [`FtxClientBuilder`].
+    pub fn builder() -> FtxClientBuilder {
+        FtxClientBuilder::new()
+    }
+
     pub fn with_auth(
         public_key: &str,
         private_key: &str,
         subaccount: Option<String>,
     ) -> Result<Self> {
-        Ok(Self {
-            auth: Some(Auth {
-                private_key: private_key.into(),
-                public_key: public_key.into(),
-                subaccount,
-            }),
-            client: Default::default(),
-        })
+        Self::builder()
+            .auth(public_key, private_key)
+            .subaccount(subaccount)
+            .build()
+    }
+
+    /// Base url REST requests are sent to, e.g. `https://ftx.com/api`.
+    pub fn rest_url(&self) -> &str {
+        &self.config.rest_url
+    }
+
+    /// Url websocket connections are opened to, e.g. `wss://ftx.com/ws/`.
+    pub fn websocket_url(&self) -> &str {
+        &self.config.ws_url
     }
 
     pub fn change_subaccount(&mut self, subaccount: Option<String>) -> Result<()> {
         self.auth
             .as_mut()
             .map(|auth| auth.subaccount = subaccount)
-            .ok_or_else(|| anyhow!("no auth data present, can't change subaccount"))
+            .ok_or(FtxError::MissingAuth)
+    }
+
+    /// Handle acting on `subaccount`, or on the main account for `None`.
+    /// The connection pool, rate limiter and other settings are shared with `self`.
+    pub fn with_subaccount(&self, subaccount: Option<&str>) -> Result<Self> {
+        let mut client = self.clone();
+        client.change_subaccount(subaccount.map(str::to_owned))?;
+        Ok(client)
+    }
+
+    /// Send a single request on behalf of `subaccount`, or of the main account for `None`,
+    /// regardless of the subaccount the client is configured with.
+    pub async fn request_as<Q: Request>(
+        &self,
+        subaccount: Option<&str>,
+        request: Q,
+    ) -> Result<Q::Response> {
+        self.with_subaccount(subaccount)?.request(request).await
     }
 
-    fn attach_auth_headers<B: HeaderBuilder>(
+    async fn attach_auth_headers<B: HeaderBuilder>(
         &self,
         builder: B,
         method: Method,
         api_path: &str,
         body: Option<&str>,
     ) -> Result<B> {
-        let auth = self
-            .auth
-            .as_ref()
-            .ok_or_else(|| anyhow!("missing auth data"))?;
+        let auth = self.auth.as_ref().ok_or(FtxError::MissingAuth)?;
 
-        let timestamp = Utc::now().timestamp_millis();
+        let timestamp = self.clock.now_ms();
 
         let prehash = format!(
             "{}{}{}{}",
@@ -97,88 +221,285 @@ impl FtxClient {
             api_path.strip_suffix('?').unwrap_or(api_path),
             body.unwrap_or("")
         );
-        let signature = auth.sign(&prehash)?;
+        let signature = auth.signer.sign(&prehash).await?;
 
-        Ok(builder
+        let builder = builder
             .add_header("FTX-KEY", &auth.public_key)
             .add_header("FTX-TS", &timestamp.to_string())
-            .add_header("FTX-SIGN", &signature))
+            .add_header("FTX-SIGN", &signature);
+        Ok(match &auth.subaccount {
+            Some(subaccount) => {
+                builder.add_header("FTX-SUBACCOUNT", &encode_uri_component(subaccount))
+            }
+            None => builder,
+        })
     }
 
     pub async fn request<Q: Request>(&self, request: Q) -> Result<Q::Response> {
+        Ok(self.request_schema(&request).await?.result)
+    }
+
+    /// Like [`request`](Self::request), also returning the status, headers,
+    /// latency and body of the response.
+    pub async fn request_with_meta<Q: Request>(
+        &self,
+        request: Q,
+    ) -> Result<ApiResponse<Q::Response>> {
+        let request = self.prepare(&request)?;
+        self.traced(&request, async {
+            let resp = self.dispatch(&request).await?;
+            let ResponseSchema {
+                result,
+                has_more_data,
+                ..
+            } = parse_response(resp.status, resp.body.clone())?;
+
+            Ok(ApiResponse {
+                result,
+                has_more_data,
+                status: resp.status,
+                headers: resp.headers,
+                latency: resp.latency,
+                raw_body: resp.body,
+            })
+        })
+        .await
+    }
+
+    async fn request_schema<Q: Request>(&self, request: &Q) -> Result<ResponseSchema<Q::Response>> {
+        let request = self.prepare(request)?;
+        self.traced(&request, async {
+            let resp = self.dispatch(&request).await?;
+            parse_response(resp.status, resp.body)
+        })
+        .await
+    }
+
+    fn prepare<Q: Request>(&self, request: &Q) -> Result<PreparedRequest> {
+        request.validate()?;
         let endpoint = request.render_endpoint();
-        let url = format!("{}{}", API_URL, &endpoint);
+        let url = format!("{}{}", self.config.rest_url, &endpoint);
 
-        let (req, req_path, req_body) = match Q::METHOD {
-            Method::GET => {
-                let url = Url::parse_with_params(&url, request.to_url_query())?;
-                let path = url.path().to_owned();
-                let path = match url.query() {
-                    Some(q) => path + "?" + q,
-                    None => path,
-                };
-                debug!("sending GET message, url: {}", &url.as_str());
-                (self.client.request(Q::METHOD, url.as_str()), path, None)
+        let (url, path, body) = match Q::METHOD {
+            Method::GET | Method::HEAD => {
+                let query = request.to_url_query().map_err(FtxError::Serialize)?;
+                let mut url = Url::parse_with_params(&url, query)?;
+                // without parameters the url would end with an empty `?`
+                if url.query() == Some("") {
+                    url.set_query(None);
+                }
+                let path = path_and_query(&url);
+                debug!("sending {} message, url: {}", Q::METHOD, &url.as_str());
+                (url, path, None)
             }
-            Method::POST | Method::DELETE => {
+            _ => {
                 let url = Url::parse(&url)?;
-                let request_body = serde_json::to_string(&request)?;
+                let request_body = serde_json::to_string(&request).map_err(FtxError::Serialize)?;
                 debug!(
-                    "sending POST message, url: {:?}, body: {:?}",
-                    &url, request_body,
+                    "sending {} message, url: {:?}, body: {:?}",
+                    Q::METHOD,
+                    &url,
+                    request_body,
                 );
-                (
-                    self.client
-                        .request(Q::METHOD, url.as_str())
-                        .body(request_body.clone())
-                        .header("content-type", "application/json"),
-                    url.path().to_owned(),
-                    Some(request_body),
-                )
+                let path = url.path().to_owned();
+                (url, path, Some(request_body))
             }
-            _ => panic!("Request trait specified an unsupported method"),
         };
 
-        let req = req.header("user-agent", "ftx-rs");
+        let retryable = Q::METHOD == Method::GET
+            || (self.retry_policy.retries_with_client_id() && request.client_id().is_some());
+
+        Ok(PreparedRequest {
+            method: Q::METHOD,
+            url,
+            path,
+            body,
+            signed: Q::NEEDS_AUTH,
+            class: Q::CLASS,
+            retryable,
+        })
+    }
 
-        let req = if Q::NEEDS_AUTH {
-            self.attach_auth_headers(req, Q::METHOD, &req_path, req_body.as_deref())?
+    /// Call an endpoint that has no [`Request`] type, e.g. one added to FTX recently.
+    ///
+    /// `path` is relative to the REST url, like `/wallet/balances`. Signing, subaccount,
+    /// retries and error handling are the same as for [`request`](Self::request),
+    /// `result` of the response is returned as `T`, e.g. [`serde_json::Value`].
+    pub async fn raw_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+        signed: bool,
+    ) -> Result<T> {
+        let url = format!("{}{}", self.config.rest_url, path);
+        let url = if query.is_empty() {
+            Url::parse(&url)?
         } else {
-            req
+            Url::parse_with_params(&url, query)?
         };
+        let body = body
+            .map(|body| serde_json::to_string(body).map_err(FtxError::Serialize))
+            .transpose()?;
 
-        log::debug!("{:?}", req);
+        let request = PreparedRequest {
+            retryable: method == Method::GET,
+            method,
+            path: path_and_query(&url),
+            url,
+            body,
+            signed,
+            class: RequestClass::Other,
+        };
+        self.traced(&request, async {
+            let resp = self.dispatch(&request).await?;
+            Ok(parse_response(resp.status, resp.body)?.result)
+        })
+        .await
+    }
 
-        self.handle_response(req.send().await?).await
+    /// Send a request after refreshing the clock offset it will be signed with.
+    async fn dispatch(&self, request: &PreparedRequest) -> Result<RawResponse> {
+        if request.signed {
+            self.refresh_clock().await;
+        }
+        self.send(request).await
     }
 
-    async fn handle_response<T: DeserializeOwned + std::fmt::Debug>(
-        &self,
-        resp: Response,
-    ) -> Result<T> {
-        if resp.status().is_success() {
-            let resp = resp.text().await?;
-            println!("got message: {}", &resp);
-            debug!("got message: {}", &resp);
-            match from_str::<ResponseSchema<T>>(&resp) {
-                Ok(resp) => {
-                    if resp.success {
-                        Ok(resp.result)
-                    } else {
-                        Err(anyhow!("success = false in response: {:?}", resp))
-                    }
+    /// Send a request, retrying transient failures according to the retry policy.
+    async fn send(&self, request: &PreparedRequest) -> Result<RawResponse> {
+        let mut attempt = 1;
+        loop {
+            if let Some(limiter) = &self.rate_limiter {
+                let key = self.auth.as_ref().map_or("", |auth| &auth.public_key);
+                limiter.acquire(key, request.class).await;
+            }
+
+            let info = RequestInfo {
+                method: &request.method,
+                url: &request.url,
+                path: &request.path,
+                body: request.body.as_deref(),
+                signed: request.signed,
+                attempt,
+            };
+            self.config.interceptors.on_request(&info);
+            let started = Instant::now();
+            let resp = self.send_once(request).await;
+            self.config
+                .interceptors
+                .on_response(&info, &resp, started.elapsed());
+            trace::record_attempt(attempt, &resp);
+
+            let delay = if request.retryable {
+                self.retry_policy.retry_delay(attempt, &resp)
+            } else {
+                None
+            };
+            match delay {
+                Some(delay) => {
+                    debug!(
+                        "retrying {} {} in {:?}, attempt {} failed",
+                        request.method, request.path, delay, attempt
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
                 }
-                Err(e) => Err(anyhow!("error {} while deserializing {}", e, resp)),
+                None => return resp,
             }
-        } else {
-            let resp_e = resp.error_for_status_ref().unwrap_err();
-            Err(anyhow!(
-                "http error: {}; body: {};",
-                resp_e,
-                resp.text().await?
-            ))
         }
     }
+
+    async fn send_once(&self, request: &PreparedRequest) -> Result<RawResponse> {
+        if let Some(Cassette::Replay(replayer)) = self.config.cassette.as_deref() {
+            return replayer.respond(request);
+        }
+
+        let mut req = self
+            .client
+            .request(request.method.clone(), request.url.clone())
+            .header("user-agent", &self.config.user_agent);
+
+        if let Some(body) = &request.body {
+            req = req
+                .body(body.clone())
+                .header("content-type", "application/json");
+        }
+
+        if request.signed {
+            req = self
+                .attach_auth_headers(
+                    req,
+                    request.method.clone(),
+                    &request.path,
+                    request.body.as_deref(),
+                )
+                .await?;
+        }
+
+        log::debug!("{:?}", req);
+
+        let req = req.build()?;
+        let sent_headers = req.headers().clone();
+        let started = Instant::now();
+        let resp = self.client.execute(req).await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.text().await?;
+        debug!("got message: {}", &body);
+
+        let resp = RawResponse {
+            status,
+            headers,
+            body,
+            latency: started.elapsed(),
+        };
+        if let Some(Cassette::Record(recorder)) = self.config.cassette.as_deref() {
+            recorder.record(request, &sent_headers, &resp)?;
+        }
+        Ok(resp)
+    }
+}
+
+/// Path and query of `url` as used in signatures.
+fn path_and_query(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_owned(),
+    }
+}
+
+fn parse_response<T: DeserializeOwned>(
+    status: StatusCode,
+    resp: String,
+) -> Result<ResponseSchema<T>> {
+    if let Ok(ErrorSchema {
+        success: false,
+        error,
+    }) = from_str(&resp)
+    {
+        return Err(FtxError::Api {
+            status,
+            error: error.unwrap_or(resp),
+        });
+    }
+
+    if !status.is_success() {
+        return Err(FtxError::Http { status, body: resp });
+    }
+
+    match from_str::<ResponseSchema<T>>(&resp) {
+        Ok(resp) if resp.success => Ok(resp),
+        Ok(_) => Err(FtxError::Api {
+            status,
+            error: resp,
+        }),
+        Err(source) => Err(FtxError::Deserialize {
+            source,
+            payload: resp,
+        }),
+    }
 }
 
 #[cfg(test)]
@@ -188,6 +509,9 @@ mod tests {
     use rust_decimal::Decimal;
     use rust_decimal::prelude::FromStr;
     use crate::request::{ModifyOrder, OrderRequestId};
+    use crate::FtxError;
+    use reqwest::StatusCode;
+    use super::parse_response;
 
     #[test]
     fn decimal_deserialisation() {
@@ -217,4 +541,31 @@ mod tests {
         let result = to_string::<ModifyOrder>(&order).unwrap();
         assert_eq!(format!(r#"{{"price":{},"size":null,"clientId":null}}"#, decimal), result);
     }
+
+    #[test]
+    fn api_error_parsing() {
+        let body = r#"{"success":false,"error":"Not enough balances"}"#;
+        match parse_response::<()>(StatusCode::BAD_REQUEST, body.into()) {
+            Err(FtxError::Api { status, error }) => {
+                assert_eq!(StatusCode::BAD_REQUEST, status);
+                assert_eq!("Not enough balances", error);
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        match parse_response::<()>(StatusCode::BAD_GATEWAY, "<html></html>".into()) {
+            Err(FtxError::Http { status, body }) => {
+                assert_eq!(StatusCode::BAD_GATEWAY, status);
+                assert_eq!("<html></html>", body);
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        match parse_response::<u64>(StatusCode::OK, r#"{"success":true,"result":"x"}"#.into()) {
+            Err(FtxError::Deserialize { payload, .. }) => {
+                assert_eq!(r#"{"success":true,"result":"x"}"#, payload)
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
 }
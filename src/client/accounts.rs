@@ -0,0 +1,165 @@
+use futures::future;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+use super::FtxClient;
+use crate::{
+    error::{FtxError, Result},
+    model::{AccountInformation, Balance, Order},
+    request::{self, Request},
+};
+
+/// Named clients for several API keys and subaccounts, with views combined over all of them.
+///
+/// ```no_run
+/// # async fn run() -> ftx_rs::Result<()> {
+/// use ftx_rs::{request, FtxAccounts, FtxClient};
+///
+/// let main = FtxClient::builder().auth("key", "secret").build()?;
+/// let mut accounts = FtxAccounts::new();
+/// accounts.insert("bot", main.with_subaccount(Some("bot"))?);
+/// accounts.insert("main", main);
+///
+/// let orders = accounts.request("bot", request::OpenOrders { market: None }).await?;
+/// let balances = accounts.balances().await;
+/// for balance in balances.combined() {
+///     println!("{} {}", balance.coin, balance.total);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FtxAccounts {
+    clients: BTreeMap<String, FtxClient>,
+}
+
+/// Results of a request sent to every account of an [`FtxAccounts`], by account name.
+#[derive(Debug)]
+pub struct PerAccount<T> {
+    pub results: BTreeMap<String, T>,
+    /// Accounts the request failed for, they are missing from `results`
+    pub errors: BTreeMap<String, FtxError>,
+}
+
+impl FtxAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `client` as `name`, returning the client previously registered under it.
+    pub fn insert(&mut self, name: impl Into<String>, client: FtxClient) -> Option<FtxClient> {
+        self.clients.insert(name.into(), client)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<FtxClient> {
+        self.clients.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FtxClient> {
+        self.clients.get(name)
+    }
+
+    /// Account names with their clients, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FtxClient)> {
+        self.clients
+            .iter()
+            .map(|(name, client)| (name.as_str(), client))
+    }
+
+    /// Send `request` with the client registered as `account`.
+    pub async fn request<Q: Request>(&self, account: &str, request: Q) -> Result<Q::Response> {
+        let client = self
+            .get(account)
+            .ok_or_else(|| FtxError::UnknownAccount(account.into()))?;
+        client.request(request).await
+    }
+
+    /// Send `request` with every client concurrently.
+    pub async fn request_all<Q: Request + Clone>(&self, request: Q) -> PerAccount<Q::Response> {
+        let responses = future::join_all(
+            self.clients
+                .values()
+                .map(|client| client.request(request.clone())),
+        )
+        .await;
+
+        let mut per_account = PerAccount {
+            results: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        };
+        for (name, response) in self.clients.keys().zip(responses) {
+            match response {
+                Ok(response) => {
+                    per_account.results.insert(name.clone(), response);
+                }
+                Err(e) => {
+                    per_account.errors.insert(name.clone(), e);
+                }
+            }
+        }
+        per_account
+    }
+
+    /// Wallet balances of every account.
+    pub async fn balances(&self) -> PerAccount<Vec<Balance>> {
+        self.request_all(request::Balances).await
+    }
+
+    pub async fn account_information(&self) -> PerAccount<AccountInformation> {
+        self.request_all(request::AccountInformation).await
+    }
+
+    /// Open orders of every account, optionally only those of `market`.
+    pub async fn open_orders(&self, market: Option<&str>) -> PerAccount<Vec<Order>> {
+        self.request_all(request::OpenOrders { market }).await
+    }
+}
+
+impl PerAccount<Vec<Balance>> {
+    /// Balances summed per coin over all accounts, ordered by coin.
+    pub fn combined(&self) -> Vec<Balance> {
+        let mut combined: BTreeMap<&str, Balance> = BTreeMap::new();
+        for balance in self.results.values().flatten() {
+            let sum = combined.entry(&balance.coin).or_insert_with(|| Balance {
+                coin: balance.coin.clone(),
+                free: Decimal::ZERO,
+                total: Decimal::ZERO,
+                usd_value: 0.0,
+                spot_borrow: Decimal::ZERO,
+                available_without_borrow: Decimal::ZERO,
+            });
+            sum.free += balance.free;
+            sum.total += balance.total;
+            sum.usd_value += balance.usd_value;
+            sum.spot_borrow += balance.spot_borrow;
+            sum.available_without_borrow += balance.available_without_borrow;
+        }
+        combined.into_values().collect()
+    }
+}
+
+impl PerAccount<AccountInformation> {
+    pub fn collateral(&self) -> Decimal {
+        self.results.values().map(|info| info.collateral).sum()
+    }
+
+    pub fn free_collateral(&self) -> Decimal {
+        self.results.values().map(|info| info.free_collateral).sum()
+    }
+
+    pub fn total_account_value(&self) -> f64 {
+        self.results
+            .values()
+            .map(|info| info.total_account_value)
+            .sum()
+    }
+}
+
+impl PerAccount<Vec<Order>> {
+    /// Orders of all accounts with the name of their account, ordered by account name.
+    pub fn combined(&self) -> impl Iterator<Item = (&str, &Order)> {
+        self.results
+            .iter()
+            .flat_map(|(name, orders)| orders.iter().map(move |order| (name.as_str(), order)))
+    }
+}
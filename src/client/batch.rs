@@ -0,0 +1,39 @@
+use futures::stream::{self, StreamExt};
+
+use super::FtxClient;
+use crate::{error::Result, request::Request};
+
+impl FtxClient {
+    /// Send `requests` with at most `concurrency` of them in flight at once,
+    /// returning their results in the order of `requests`.
+    ///
+    /// A failed request doesn't stop the others. Each request still waits for
+    /// the rate limiter and is retried like one sent with [`request`](Self::request).
+    ///
+    /// ```no_run
+    /// # async fn run(client: ftx_rs::FtxClient) {
+    /// use ftx_rs::request::SubaccountBalances;
+    ///
+    /// let nicknames = ["bot 1", "bot 2", "bot 3"];
+    /// let balances = client
+    ///     .batch(
+    ///         nicknames
+    ///             .iter()
+    ///             .map(|nickname| SubaccountBalances { nickname }),
+    ///         4,
+    ///     )
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn batch<Q: Request>(
+        &self,
+        requests: impl IntoIterator<Item = Q>,
+        concurrency: usize,
+    ) -> Vec<Result<Q::Response>> {
+        stream::iter(requests)
+            .map(|request| self.request(request))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+}
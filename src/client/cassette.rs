@@ -0,0 +1,234 @@
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Mutex,
+    time::Duration,
+};
+
+use super::{PreparedRequest, RawResponse};
+use crate::error::{FtxError, Result};
+
+/// Headers that identify the account or can be replayed, never written to a cassette.
+const SCRUBBED_HEADERS: [&str; 3] = ["ftx-key", "ftx-sign", "ftx-ts"];
+const SCRUBBED: &str = "[scrubbed]";
+
+/// One request/response pair, stored as a json line in a cassette file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Interaction {
+    method: String,
+    /// Path and query, without the host
+    path: String,
+    body: Option<String>,
+    /// Request headers, for reading the cassette, they are not matched on replay
+    headers: BTreeMap<String, String>,
+    status: u16,
+    #[serde(default)]
+    response_headers: Vec<(String, String)>,
+    response: String,
+}
+
+impl Interaction {
+    fn matches(&self, request: &PreparedRequest) -> bool {
+        self.method == request.method.as_str()
+            && self.path == request.path
+            && self.body == request.body
+    }
+}
+
+/// Writes every request made by a client to a cassette file, replacing its previous contents.
+#[derive(Debug)]
+pub(super) struct Recorder {
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    pub(super) fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| FtxError::Cassette(format!("can't open {}: {}", path.display(), e)))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub(super) fn record(
+        &self,
+        request: &PreparedRequest,
+        headers: &HeaderMap,
+        response: &RawResponse,
+    ) -> Result<()> {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if SCRUBBED_HEADERS.contains(&name.as_str()) {
+                    SCRUBBED
+                } else {
+                    value.to_str().unwrap_or(SCRUBBED)
+                };
+                (name.as_str().to_owned(), value.to_owned())
+            })
+            .collect();
+        let response_headers = response
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+            })
+            .collect();
+
+        let interaction = Interaction {
+            method: request.method.to_string(),
+            path: request.path.clone(),
+            body: request.body.clone(),
+            headers,
+            status: response.status.as_u16(),
+            response_headers,
+            response: response.body.clone(),
+        };
+        let line = serde_json::to_string(&interaction).map_err(FtxError::Serialize)?;
+
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{}", line)
+            .and_then(|_| file.flush())
+            .map_err(|e| FtxError::Cassette(format!("failed to record interaction: {}", e)))
+    }
+}
+
+/// Answers requests from a cassette file instead of the network.
+#[derive(Debug)]
+pub(super) struct Replayer {
+    /// Interactions not served yet, identical requests are answered in recorded order.
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl Replayer {
+    pub(super) fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| FtxError::Cassette(format!("can't open {}: {}", path.display(), e)))?;
+
+        let interactions = BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|(i, line)| {
+                let line = line.map_err(|e| FtxError::Cassette(e.to_string()))?;
+                serde_json::from_str(&line)
+                    .map(Some)
+                    .map_err(|e| FtxError::Cassette(format!("{}:{}: {}", path.display(), i + 1, e)))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    pub(super) fn respond(&self, request: &PreparedRequest) -> Result<RawResponse> {
+        let mut interactions = self.interactions.lock().unwrap();
+        let interaction = interactions
+            .iter_mut()
+            .find(|i| i.as_ref().is_some_and(|i| i.matches(request)))
+            .and_then(Option::take)
+            .ok_or_else(|| {
+                FtxError::Cassette(format!(
+                    "no recorded interaction for {} {} with body {}",
+                    request.method,
+                    request.path,
+                    request.body.as_deref().unwrap_or("<none>")
+                ))
+            })?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &interaction.response_headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| FtxError::Cassette(e.to_string()))?;
+            let value =
+                HeaderValue::from_str(value).map_err(|e| FtxError::Cassette(e.to_string()))?;
+            headers.append(name, value);
+        }
+
+        Ok(RawResponse {
+            status: StatusCode::from_u16(interaction.status)
+                .map_err(|e| FtxError::Cassette(e.to_string()))?,
+            headers,
+            body: interaction.response,
+            latency: Duration::ZERO,
+        })
+    }
+}
+
+/// Where a client's traffic is recorded to or replayed from.
+#[derive(Debug)]
+pub(super) enum Cassette {
+    Record(Recorder),
+    Replay(Replayer),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Recorder, Replayer};
+    use crate::client::{PreparedRequest, RawResponse};
+    use crate::request::RequestClass;
+    use crate::FtxError;
+    use reqwest::{header::HeaderMap, Method, StatusCode};
+    use std::time::Duration;
+
+    #[test]
+    fn replays_recorded_interactions() {
+        let path =
+            std::env::temp_dir().join(format!("ftx-rs-cassette-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let request = PreparedRequest {
+            method: Method::GET,
+            url: "https://ftx.com/api/wallet/balances".parse().unwrap(),
+            path: "/api/wallet/balances".into(),
+            body: None,
+            signed: true,
+            class: RequestClass::Other,
+            retryable: true,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("ftx-key", "public key".parse().unwrap());
+        headers.insert("ftx-sign", "signature".parse().unwrap());
+        let mut response = RawResponse {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            headers: HeaderMap::new(),
+            body: r#"{"success":false,"error":"Please retry request"}"#.into(),
+            latency: Duration::from_millis(20),
+        };
+        response.headers.insert("retry-after", "2".parse().unwrap());
+
+        // recording again replaces the earlier recording
+        for _ in 0..2 {
+            Recorder::create(&path)
+                .unwrap()
+                .record(&request, &headers, &response)
+                .unwrap();
+        }
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(1, cassette.lines().count());
+        assert!(!cassette.contains("public key") && !cassette.contains("signature"));
+
+        let replayer = Replayer::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let replayed = replayer.respond(&request).unwrap();
+        assert_eq!(response.status, replayed.status);
+        assert_eq!(response.headers, replayed.headers);
+        assert_eq!(response.body, replayed.body);
+        match replayer.respond(&request) {
+            Err(FtxError::Cassette(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+}
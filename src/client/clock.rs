@@ -0,0 +1,108 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::debug;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::{parse_response, FtxClient};
+use crate::{error::Result, request::ServerTime};
+
+#[derive(Debug, Default)]
+struct ClockState {
+    /// Server time minus local time, in milliseconds
+    offset_ms: Option<i64>,
+    refreshed: Option<Instant>,
+}
+
+/// Estimated offset of the exchange clock, shared between clones of a client.
+#[derive(Debug, Default)]
+pub(super) struct Clock {
+    state: Mutex<ClockState>,
+    refresh_interval: Option<Duration>,
+}
+
+impl Clock {
+    pub(super) fn new(refresh_interval: Option<Duration>) -> Self {
+        Self {
+            state: Default::default(),
+            refresh_interval,
+        }
+    }
+
+    /// Local time corrected by the measured offset, in unix milliseconds.
+    pub(super) fn now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.state.lock().unwrap().offset_ms.unwrap_or(0)
+    }
+
+    fn offset(&self) -> Option<ChronoDuration> {
+        self.state
+            .lock()
+            .unwrap()
+            .offset_ms
+            .map(ChronoDuration::milliseconds)
+    }
+
+    /// Whether the offset should be measured again, marks it as refreshed if so
+    /// to avoid concurrent requests all measuring at once.
+    fn claim_refresh(&self) -> bool {
+        let interval = match self.refresh_interval {
+            Some(interval) => interval,
+            None => return false,
+        };
+        let mut state = self.state.lock().unwrap();
+        match state.refreshed {
+            Some(refreshed) if refreshed.elapsed() < interval => false,
+            _ => {
+                state.refreshed = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    fn set_offset(&self, offset_ms: i64) {
+        let mut state = self.state.lock().unwrap();
+        state.offset_ms = Some(offset_ms);
+        state.refreshed = Some(Instant::now());
+    }
+}
+
+impl FtxClient {
+    /// Measure the offset between the exchange clock and the local one using
+    /// the `/time` endpoint. The offset is applied to all request and websocket
+    /// login timestamps from then on.
+    pub async fn sync_clock(&self) -> Result<ChronoDuration> {
+        // not going through `request` which refreshes the clock itself, but in a
+        // span of its own so a refresh doesn't overwrite the span of the request
+        // that triggered it
+        let request = self.prepare(&ServerTime)?;
+        let sent = Utc::now();
+        let resp = self.traced(&request, self.send(&request)).await?;
+        let received = Utc::now();
+        let server_time = parse_response::<DateTime<Utc>>(resp.status, resp.body)?.result;
+
+        // assume the server read its clock halfway through the round trip
+        let local_time = sent + (received - sent) / 2;
+        let offset = server_time - local_time;
+        debug!("exchange clock offset is {}ms", offset.num_milliseconds());
+
+        self.clock.set_offset(offset.num_milliseconds());
+        Ok(offset)
+    }
+
+    /// Last measured offset of the exchange clock relative to the local one,
+    /// positive if the local clock is behind. `None` if it was never measured.
+    pub fn clock_skew(&self) -> Option<ChronoDuration> {
+        self.clock.offset()
+    }
+
+    /// Re-measure the clock offset if periodic syncing is enabled and the
+    /// last measurement is stale. Failures keep the previous offset.
+    pub(super) async fn refresh_clock(&self) {
+        if self.clock.claim_refresh() {
+            if let Err(e) = self.sync_clock().await {
+                debug!("failed to sync exchange clock: {}", e);
+            }
+        }
+    }
+}
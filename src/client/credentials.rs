@@ -0,0 +1,226 @@
+use std::{collections::HashMap, env, fmt, fs, path::Path};
+use zeroize::Zeroize;
+
+use crate::error::{FtxError, Result};
+
+/// Environment variable holding the API key read by [`Credentials::from_env`].
+const PUBLIC_KEY_VAR: &str = "FTX_PUBLIC";
+/// Environment variable holding the API secret read by [`Credentials::from_env`].
+const PRIVATE_KEY_VAR: &str = "FTX_PRIVATE";
+/// Optional environment variable holding the subaccount read by [`Credentials::from_env`].
+const SUBACCOUNT_VAR: &str = "FTX_SUBACCOUNT";
+
+/// String that is never printed by `Debug` and is wiped from memory when dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(secret: &str) -> Self {
+        Self(secret.into())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Secret([redacted])")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// API key, secret and optional subaccount, passed to
+/// [`FtxClientBuilder::credentials`](crate::FtxClientBuilder::credentials).
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub public_key: String,
+    pub private_key: Secret,
+    pub subaccount: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(public_key: impl Into<String>, private_key: impl Into<Secret>) -> Self {
+        Self {
+            public_key: public_key.into(),
+            private_key: private_key.into(),
+            subaccount: None,
+        }
+    }
+
+    pub fn subaccount(mut self, subaccount: impl Into<String>) -> Self {
+        self.subaccount = Some(subaccount.into());
+        self
+    }
+
+    /// Read credentials from `FTX_PUBLIC`, `FTX_PRIVATE` and, if set, `FTX_SUBACCOUNT`.
+    pub fn from_env() -> Result<Self> {
+        let var = |name| env::var(name).map_err(|e| var_error(name, e));
+        Ok(Self {
+            public_key: var(PUBLIC_KEY_VAR)?,
+            private_key: var(PRIVATE_KEY_VAR)?.into(),
+            subaccount: env::var(SUBACCOUNT_VAR).ok().filter(|s| !s.is_empty()),
+        })
+    }
+
+    /// Read the credentials of `profile` from a file of named sections:
+    ///
+    /// ```text
+    /// [default]
+    /// key = ...
+    /// secret = ...
+    ///
+    /// [trading]
+    /// key = ...
+    /// secret = ...
+    /// subaccount = bot
+    /// ```
+    ///
+    /// On unix the file is rejected if it can be read or written by group or others.
+    pub fn from_file(path: impl AsRef<Path>, profile: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let error = |msg: String| FtxError::Credentials(format!("{}: {}", path.display(), msg));
+
+        check_permissions(path).map_err(error)?;
+        let mut contents = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let credentials = parse_profile(&contents, profile).map_err(error);
+        contents.zeroize();
+        credentials
+    }
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> std::result::Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)
+        .map_err(|e| e.to_string())?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(format!(
+            "permissions {:o} are too open, the file must only be accessible by its owner",
+            mode & 0o777
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> std::result::Result<(), String> {
+    Ok(())
+}
+
+fn parse_profile(contents: &str, profile: &str) -> std::result::Result<Credentials, String> {
+    let mut section = None;
+    let mut values = HashMap::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(name.trim());
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected `key = value`", i + 1))?;
+        if section == Some(profile) {
+            values.insert(key.trim(), value.trim());
+        }
+    }
+
+    let mut take = |key| {
+        values
+            .remove(key)
+            .ok_or_else(|| format!("profile `{}` has no `{}`", profile, key))
+    };
+    Ok(Credentials {
+        public_key: take("key")?.into(),
+        private_key: take("secret")?.into(),
+        subaccount: take("subaccount").ok().map(str::to_owned),
+    })
+}
+
+/// Error for an unreadable environment variable. `VarError`'s `Display` would
+/// include the value of a non unicode variable, which may be the secret.
+fn var_error(name: &str, error: env::VarError) -> FtxError {
+    let problem = match error {
+        env::VarError::NotPresent => "is not set",
+        env::VarError::NotUnicode(_) => "is not valid unicode",
+    };
+    FtxError::Credentials(format!("{} {}", name, problem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_profile, var_error, Credentials, Secret, PRIVATE_KEY_VAR};
+
+    #[test]
+    fn secrets_are_redacted() {
+        let credentials = Credentials::new("public", "very secret");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("public"));
+        assert!(!debug.contains("very secret"));
+        assert_eq!("very secret", credentials.private_key.expose());
+        assert_eq!("Secret([redacted])", format!("{:?}", Secret::new("x")));
+    }
+
+    #[test]
+    fn profiles() {
+        let contents = "
+            # main account
+            [default]
+            key = main-key
+            secret = main-secret
+
+            [bot]
+            key = bot-key
+            secret = bot-secret
+            subaccount = market maker
+        ";
+
+        let default = parse_profile(contents, "default").unwrap();
+        assert_eq!("main-key", default.public_key);
+        assert_eq!("main-secret", default.private_key.expose());
+        assert_eq!(None, default.subaccount);
+
+        let bot = parse_profile(contents, "bot").unwrap();
+        assert_eq!("bot-key", bot.public_key);
+        assert_eq!(Some("market maker".into()), bot.subaccount);
+
+        assert!(parse_profile(contents, "missing").is_err());
+        assert!(parse_profile("[default]\nkey", "default").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn env_errors_hide_values() {
+        use std::{env::VarError, ffi::OsString, os::unix::ffi::OsStringExt};
+
+        let value = OsString::from_vec(b"very secret \xff".to_vec());
+        let error = var_error(PRIVATE_KEY_VAR, VarError::NotUnicode(value)).to_string();
+        assert!(error.contains("FTX_PRIVATE is not valid unicode"));
+        assert!(!error.contains("very secret"));
+    }
+}
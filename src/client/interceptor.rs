@@ -0,0 +1,91 @@
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use std::{fmt, sync::Arc, time::Duration};
+use url::Url;
+
+use super::RawResponse;
+use crate::error::{FtxError, Result};
+
+/// Outgoing REST request as seen by an [`Interceptor`].
+#[derive(Debug, Clone, Copy)]
+pub struct RequestInfo<'a> {
+    pub method: &'a Method,
+    pub url: &'a Url,
+    /// Path and query, e.g. `/api/orders?market=BTC-PERP`
+    pub path: &'a str,
+    pub body: Option<&'a str>,
+    /// Whether the request carries auth headers
+    pub signed: bool,
+    /// Starts at 1 and increases when the request is retried
+    pub attempt: u32,
+}
+
+/// Response to a REST request as seen by an [`Interceptor`].
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseInfo<'a> {
+    pub status: StatusCode,
+    pub headers: &'a HeaderMap,
+    /// Time between sending the request and receiving the whole body
+    pub latency: Duration,
+    pub body: &'a str,
+}
+
+/// Observes traffic of an [`FtxClient`](crate::FtxClient), for logging, auditing or metrics.
+///
+/// Interceptors are registered with
+/// [`FtxClientBuilder::interceptor`](crate::FtxClientBuilder::interceptor)
+/// and called in registration order for every attempt of every request.
+pub trait Interceptor: Send + Sync {
+    fn on_request(&self, _request: &RequestInfo) {}
+
+    fn on_response(&self, _request: &RequestInfo, _response: &ResponseInfo) {}
+
+    /// The request failed before a response was received.
+    fn on_error(&self, _request: &RequestInfo, _error: &FtxError, _latency: Duration) {}
+}
+
+#[derive(Clone, Default)]
+pub(super) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Interceptors({})", self.0.len())
+    }
+}
+
+impl Interceptors {
+    pub(super) fn push(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.0.push(interceptor);
+    }
+
+    pub(super) fn on_request(&self, request: &RequestInfo) {
+        for interceptor in &self.0 {
+            interceptor.on_request(request);
+        }
+    }
+
+    pub(super) fn on_response(
+        &self,
+        request: &RequestInfo,
+        response: &Result<RawResponse>,
+        latency: Duration,
+    ) {
+        match response {
+            Ok(response) => {
+                let response = ResponseInfo {
+                    status: response.status,
+                    headers: &response.headers,
+                    latency,
+                    body: &response.body,
+                };
+                for interceptor in &self.0 {
+                    interceptor.on_response(request, &response);
+                }
+            }
+            Err(e) => {
+                for interceptor in &self.0 {
+                    interceptor.on_error(request, e, latency);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Request and websocket counters, read with [`Metrics::snapshot`] and, with the
+//! `metrics` feature, also reported through the [`metrics`](https://docs.rs/metrics) facade.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::interceptor::{Interceptor, RequestInfo, ResponseInfo};
+use crate::error::FtxError;
+#[cfg(feature = "websocket")]
+use crate::{error::Result, model::websocket::WsInMessage};
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Collects per endpoint request counts, errors and latencies of an
+/// [`FtxClient`](crate::FtxClient), and per channel message counts of its websockets.
+///
+/// Register it with [`FtxClientBuilder::metrics`](crate::FtxClientBuilder::metrics);
+/// clones share their counters, so keep one to take snapshots from.
+/// Every attempt of a retried request is counted.
+///
+/// With the `metrics` feature the same values are also emitted as
+/// `ftx_requests_total`, `ftx_request_errors_total`, `ftx_rate_limited_total`,
+/// `ftx_request_duration_seconds` and `ftx_websocket_messages_total`.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsSnapshot>>,
+}
+
+/// Counters collected by [`Metrics`] up to the time of the snapshot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Keyed by method and endpoint, e.g. `("GET", "/markets/{market}/orderbook")`.
+    /// Ids, market names and other path parameters are replaced by placeholders.
+    pub endpoints: BTreeMap<(String, String), EndpointMetrics>,
+    /// Websocket messages received per channel, other messages count as `control`
+    pub websocket_messages: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointMetrics {
+    pub requests: u64,
+    /// Failed attempts by category: `rate_limit`, `client` and `server` for
+    /// error statuses, `timeout`, `transport` or `other` when no response was received
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Responses with status 429, also counted in `errors`
+    pub rate_limited: u64,
+    pub latency: LatencyHistogram,
+}
+
+/// Distribution of request latencies, in the shape of a Prometheus histogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyHistogram {
+    /// Number of requests that took at most the given duration, cumulative like Prometheus buckets
+    pub buckets: Vec<(Duration, u64)>,
+    pub count: u64,
+    pub sum: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: LATENCY_BUCKETS
+                .iter()
+                .map(|&bound| (Duration::from_secs_f64(bound), 0))
+                .collect(),
+            count: 0,
+            sum: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, latency: Duration) {
+        for (bound, count) in &mut self.buckets {
+            if latency <= *bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += latency;
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.inner.lock().unwrap().clone()
+    }
+
+    fn record(&self, request: &RequestInfo, latency: Duration, error: Option<&'static str>) {
+        let method = request.method.as_str().to_owned();
+        let endpoint = endpoint_template(request.url.path());
+
+        #[cfg(feature = "metrics")]
+        {
+            let labels = [("method", method.clone()), ("endpoint", endpoint.clone())];
+            metrics::counter!("ftx_requests_total", &labels).increment(1);
+            metrics::histogram!("ftx_request_duration_seconds", &labels)
+                .record(latency.as_secs_f64());
+            if let Some(category) = error {
+                metrics::counter!(
+                    "ftx_request_errors_total",
+                    "method" => method.clone(),
+                    "endpoint" => endpoint.clone(),
+                    "category" => category
+                )
+                .increment(1);
+                if category == "rate_limit" {
+                    metrics::counter!("ftx_rate_limited_total", &labels).increment(1);
+                }
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let endpoint = inner.endpoints.entry((method, endpoint)).or_default();
+        endpoint.requests += 1;
+        endpoint.latency.observe(latency);
+        if let Some(category) = error {
+            *endpoint.errors.entry(category).or_default() += 1;
+            if category == "rate_limit" {
+                endpoint.rate_limited += 1;
+            }
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    pub(super) fn received(&self, message: &Result<WsInMessage>) {
+        let channel = match message {
+            Ok(WsInMessage::Partial { data }) | Ok(WsInMessage::Update { data }) => {
+                data.channel_name()
+            }
+            Ok(_) => "control",
+            Err(_) => return,
+        };
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!("ftx_websocket_messages_total", "channel" => channel).increment(1);
+
+        let mut inner = self.inner.lock().unwrap();
+        *inner
+            .websocket_messages
+            .entry(channel.to_owned())
+            .or_default() += 1;
+    }
+}
+
+impl Interceptor for Metrics {
+    fn on_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        let error = if response.status.as_u16() == 429 {
+            Some("rate_limit")
+        } else if response.status.is_server_error() {
+            Some("server")
+        } else if !response.status.is_success() {
+            Some("client")
+        } else {
+            None
+        };
+        self.record(request, response.latency, error);
+    }
+
+    fn on_error(&self, request: &RequestInfo, error: &FtxError, latency: Duration) {
+        let category = match error {
+            FtxError::Transport(e) if e.is_timeout() => "timeout",
+            FtxError::Transport(_) => "transport",
+            _ => "other",
+        };
+        self.record(request, latency, Some(category));
+    }
+}
+
+/// Path of a request relative to the API root, with path parameters replaced
+/// by placeholders so that the number of distinct endpoints stays bounded.
+fn endpoint_template(path: &str) -> String {
+    let path = path.strip_prefix("/api").unwrap_or(path);
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let segments: Vec<&str> = match segments.as_slice() {
+        // Market names such as BTC/USD keep their slash
+        ["markets", _, .., last @ ("orderbook" | "trades" | "candles")] => {
+            vec!["markets", "{market}", last]
+        }
+        ["markets", _, ..] => vec!["markets", "{market}"],
+        ["subaccounts", _, "balances"] => vec!["subaccounts", "{nickname}", "balances"],
+        ["wallet", "deposit_address", _] => vec!["wallet", "deposit_address", "{coin}"],
+        _ => {
+            let mut previous = "";
+            segments
+                .iter()
+                .map(|&segment| {
+                    let templated = if previous == "by_client_id" {
+                        "{client_id}"
+                    } else if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                        "{id}"
+                    } else {
+                        segment
+                    };
+                    previous = segment;
+                    templated
+                })
+                .collect()
+        }
+    };
+    format!("/{}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::endpoint_template;
+
+    #[test]
+    fn endpoint_templates() {
+        for (path, template) in [
+            ("/api/markets", "/markets"),
+            ("/api/markets/BTC/USD", "/markets/{market}"),
+            (
+                "/api/markets/BTC-PERP/orderbook",
+                "/markets/{market}/orderbook",
+            ),
+            ("/api/markets/BTC/USD/candles", "/markets/{market}/candles"),
+            ("/api/orders/123456", "/orders/{id}"),
+            ("/api/orders/123456/modify", "/orders/{id}/modify"),
+            (
+                "/api/orders/by_client_id/a%2Fb",
+                "/orders/by_client_id/{client_id}",
+            ),
+            ("/api/orders/history", "/orders/history"),
+            (
+                "/api/subaccounts/my%20bot/balances",
+                "/subaccounts/{nickname}/balances",
+            ),
+            (
+                "/api/wallet/deposit_address/USDT",
+                "/wallet/deposit_address/{coin}",
+            ),
+        ] {
+            assert_eq!(template, endpoint_template(path), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use std::{net::IpAddr, time::Duration};
+use url::Url;
+
+/// Connection settings applied to both REST and websocket connections.
+#[derive(Debug, Clone, Default)]
+pub(super) struct NetworkConfig {
+    /// `http://` proxy, websocket connections are tunneled through it with `CONNECT`
+    pub(super) proxy: Option<Url>,
+    pub(super) local_address: Option<IpAddr>,
+    pub(super) connect_timeout: Option<Duration>,
+    /// Limit for a whole REST request, or for opening a websocket
+    pub(super) timeout: Option<Duration>,
+}
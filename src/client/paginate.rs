@@ -0,0 +1,162 @@
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::Method;
+use serde::{ser, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::FtxClient;
+use crate::{
+    error::{FtxError, Result},
+    request::{Paginated, Request, RequestClass},
+};
+
+/// Width of the overlap between consecutive pages, in seconds. The next page ends
+/// this long after the oldest item so that rounding the fractional `end_time`
+/// never cuts off items at the same time as it.
+const OVERLAP: f64 = 0.001;
+
+struct PageState<Q> {
+    request: Q,
+    /// Fractional `end_time` of the next page, `None` until a page was received
+    end_time: Option<f64>,
+    /// Items already returned that fall into the overlap of the next page, with their times.
+    boundary: HashMap<u64, f64>,
+    done: bool,
+}
+
+/// A page of `Q` ending at a fractional `end_time`, which the integer
+/// `end_time` of the request types can't express.
+struct Page<'a, Q> {
+    request: &'a Q,
+    end_time: Option<f64>,
+}
+
+impl<Q: Serialize> Serialize for Page<'_, Q> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serde_json::to_value(self.request).map_err(ser::Error::custom)?;
+        if let (Some(end_time), Value::Object(fields)) = (self.end_time, &mut value) {
+            fields.insert("end_time".into(), end_time.into());
+        }
+        value.serialize(serializer)
+    }
+}
+
+impl<Q: Request> Request for Page<'_, Q> {
+    type Response = Q::Response;
+
+    const METHOD: Method = Q::METHOD;
+    const NEEDS_AUTH: bool = Q::NEEDS_AUTH;
+    const CLASS: RequestClass = Q::CLASS;
+
+    fn render_endpoint(&self) -> String {
+        self.request.render_endpoint()
+    }
+
+    fn validate(&self) -> crate::error::Result<()> {
+        self.request.validate()
+    }
+}
+
+impl FtxClient {
+    /// Stream all items of a history request, newest first.
+    ///
+    /// Pages are fetched lazily by moving `end_time` back to the fractional time of
+    /// the oldest item of the previous page, until a page isn't full. Items at that
+    /// time are requested again so that none are skipped, and only returned once.
+    /// Pages hold `limit` items, or the most the endpoint allows when it isn't set.
+    /// The stream fails with [`FtxError::Pagination`] if a whole page falls within
+    /// a millisecond, since moving on would skip items.
+    ///
+    /// ```no_run
+    /// # async fn run(client: ftx_rs::FtxClient) -> ftx_rs::Result<()> {
+    /// use ftx_rs::request::Trades;
+    /// use futures::TryStreamExt;
+    ///
+    /// let trades: Vec<_> = client
+    ///     .paginate(Trades {
+    ///         market_name: "BTC/USD",
+    ///         limit: Some(100),
+    ///         start_time: Some(1_600_000_000),
+    ///         end_time: None,
+    ///     })
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paginate<'a, Q>(&'a self, request: Q) -> impl Stream<Item = Result<Q::Item>> + 'a
+    where
+        Q: Paginated<Response = Vec<<Q as Paginated>::Item>> + 'a,
+        Q::Item: 'a,
+    {
+        let mut request = request;
+        if request.limit().is_none() {
+            request.set_limit(Q::MAX_LIMIT);
+        }
+        let state = PageState {
+            request,
+            end_time: None,
+            boundary: HashMap::new(),
+            done: false,
+        };
+
+        stream::try_unfold(state, move |state| self.next_page(state))
+            .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    async fn next_page<Q>(
+        &self,
+        mut state: PageState<Q>,
+    ) -> Result<Option<(Vec<Q::Item>, PageState<Q>)>>
+    where
+        Q: Paginated<Response = Vec<<Q as Paginated>::Item>>,
+    {
+        if state.done {
+            return Ok(None);
+        }
+
+        let request = Page {
+            request: &state.request,
+            end_time: state.end_time,
+        };
+        let page = self.request_schema(&request).await?;
+        let items = page.result;
+        let limit = state.request.limit().unwrap_or(Q::MAX_LIMIT);
+        let full = items.len() >= limit as usize;
+
+        let oldest = match items.iter().map(Q::item_time).reduce(f64::min) {
+            Some(oldest) => oldest,
+            None => return Ok(None),
+        };
+
+        let items: Vec<_> = items
+            .into_iter()
+            .filter(|item| !state.boundary.contains_key(&Q::item_id(item)))
+            .collect();
+
+        if items.is_empty() {
+            if full {
+                return Err(FtxError::Pagination(format!(
+                    "more than {} items within a millisecond of unix time {}",
+                    limit, oldest
+                )));
+            }
+            return Ok(None);
+        }
+
+        // Items at the oldest time may continue on the next page, which returns
+        // everything in its overlap again
+        state.boundary.retain(|_, time| *time <= oldest + OVERLAP);
+        state.boundary.extend(
+            items
+                .iter()
+                .map(|item| (Q::item_id(item), Q::item_time(item)))
+                .filter(|(_, time)| *time <= oldest + OVERLAP),
+        );
+        state.end_time = Some(oldest + OVERLAP);
+        state.done = !full || page.has_more_data == Some(false);
+
+        Ok(Some((items, state)))
+    }
+}
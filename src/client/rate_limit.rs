@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::request::RequestClass;
+
+/// Token bucket parameters: up to `burst` requests at once,
+/// refilled at one request per `interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    burst: u32,
+    interval: Duration,
+}
+
+impl Quota {
+    /// `requests` per second with an equal burst size.
+    pub fn per_second(requests: u32) -> Self {
+        let requests = requests.max(1);
+        Self {
+            burst: requests,
+            interval: Duration::from_secs(1) / requests,
+        }
+    }
+
+    /// One request every `interval` with a burst of one.
+    pub fn with_interval(interval: Duration) -> Self {
+        Self { burst: 1, interval }
+    }
+
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Take a token, or return how long to wait until one is available.
+    fn take(&mut self, quota: &Quota) -> Option<Duration> {
+        let now = Instant::now();
+        let refilled =
+            now.duration_since(self.updated).as_secs_f64() / quota.interval.as_secs_f64();
+        self.tokens = (self.tokens + refilled).min(quota.burst as f64);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(quota.interval.mul_f64(1.0 - self.tokens))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Quotas {
+    by_class: HashMap<RequestClass, Quota>,
+    by_key: HashMap<(String, RequestClass), Quota>,
+}
+
+/// Client side rate limiter, keeps one token bucket per API key and [`RequestClass`].
+///
+/// Clones share buckets, so a single limiter can be given to several clients
+/// and the client itself can be cloned freely between tasks.
+/// Requests over the limit wait for a token instead of failing.
+///
+/// ```
+/// use ftx_rs::{request::RequestClass, FtxClient, Quota, RateLimiter};
+///
+/// let limiter = RateLimiter::new()
+///     .quota(RequestClass::Order, Quota::per_second(8))
+///     .quota(RequestClass::MarketData, Quota::per_second(20).burst(40));
+/// let client = FtxClient::builder().rate_limiter(limiter).build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    quotas: Arc<Quotas>,
+    buckets: Arc<Mutex<HashMap<(String, RequestClass), Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Limit requests of `class`, applied to every API key without a more specific quota.
+    pub fn quota(mut self, class: RequestClass, quota: Quota) -> Self {
+        Arc::make_mut(&mut self.quotas)
+            .by_class
+            .insert(class, quota);
+        self
+    }
+
+    /// Limit requests of `class` sent with the API key `public_key`.
+    pub fn key_quota(mut self, public_key: &str, class: RequestClass, quota: Quota) -> Self {
+        Arc::make_mut(&mut self.quotas)
+            .by_key
+            .insert((public_key.into(), class), quota);
+        self
+    }
+
+    fn quota_for(&self, key: &str, class: RequestClass) -> Option<Quota> {
+        self.quotas
+            .by_key
+            .get(&(key.to_owned(), class))
+            .or_else(|| self.quotas.by_class.get(&class))
+            .copied()
+    }
+
+    /// Wait until a request of `class` can be sent with `key`.
+    /// Unauthenticated requests use an empty key.
+    pub(crate) async fn acquire(&self, key: &str, class: RequestClass) {
+        let quota = match self.quota_for(key, class) {
+            Some(quota) => quota,
+            None => return,
+        };
+
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                buckets
+                    .entry((key.to_owned(), class))
+                    .or_insert_with(|| Bucket {
+                        tokens: quota.burst as f64,
+                        updated: Instant::now(),
+                    })
+                    .take(&quota)
+            };
+
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Quota, RateLimiter};
+    use crate::request::RequestClass;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn waits_for_tokens() {
+        let limiter = RateLimiter::new().quota(
+            RequestClass::Order,
+            Quota::with_interval(Duration::from_millis(50)),
+        );
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire("key", RequestClass::Order).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // other classes and keys are not affected
+        let start = Instant::now();
+        limiter.acquire("key", RequestClass::MarketData).await;
+        limiter.acquire("other key", RequestClass::Order).await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
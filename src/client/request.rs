@@ -1,18 +1,167 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use reqwest::Method;
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::model::{self};
+use super::util::encode_path_segment;
+use crate::{
+    error::{FtxError, Result},
+    model::{self},
+};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
+pub mod owned;
+
+/// Group of endpoints sharing a client side rate limit, see [`RateLimiter`](crate::RateLimiter)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RequestClass {
+    /// Placing, modifying and cancelling orders
+    Order,
+    /// Public market data
+    MarketData,
+    Other,
+}
+
 pub trait Request: Serialize {
     type Response: DeserializeOwned + std::fmt::Debug;
 
     const METHOD: Method;
     const NEEDS_AUTH: bool;
+    const CLASS: RequestClass = RequestClass::Other;
 
     fn render_endpoint(&self) -> String;
+
+    /// Client order id carried by the request, lets
+    /// [`RetryPolicy::retry_with_client_id`](crate::RetryPolicy::retry_with_client_id)
+    /// retry it. The exchange only rejects a duplicate client id while the first
+    /// order is open, so requests should return `None` for orders that may be
+    /// closed by the time a failed attempt is retried.
+    fn client_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Check the parameters before the request is sent, contradictory or out of range
+    /// values fail with [`FtxError::InvalidRequest`] without reaching the exchange.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Result<()> {
+    Err(FtxError::InvalidRequest(message.into()))
+}
+
+fn check_limit(limit: Option<u32>, max: u32) -> Result<()> {
+    match limit {
+        Some(limit) if limit == 0 || limit > max => {
+            invalid(format!("limit {} is not between 1 and {}", limit, max))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_time_window(start_time: Option<i64>, end_time: Option<i64>) -> Result<()> {
+    match (start_time, end_time) {
+        (Some(start), Some(end)) if start > end => {
+            invalid(format!("start_time {} is after end_time {}", start, end))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Path parameter of an endpoint, percent-encoded so that it stays a single path segment.
+/// Use it for user supplied values in [`Request::render_endpoint`], and call
+/// [`validate`](Self::validate) from [`Request::validate`]:
+///
+/// ```
+/// use ftx_rs::request::PathParam;
+///
+/// let endpoint = format!("/orders/by_client_id/{}", PathParam("a/b?c"));
+/// assert_eq!("/orders/by_client_id/a%2Fb%3Fc", endpoint);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PathParam<'a>(pub &'a str);
+
+impl PathParam<'_> {
+    /// Fail for `.` and `..`, which URL parsing resolves as relative segments even
+    /// when percent-encoded, sending the request to a different endpoint.
+    pub fn validate(&self) -> Result<()> {
+        match self.0 {
+            "." | ".." => invalid(format!("path parameter {:?} is a dot segment", self.0)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for PathParam<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        encode_path_segment(self.0).fmt(f)
+    }
+}
+
+/// Market name in an endpoint path. Like [`PathParam`], except that the slash
+/// of spot markets such as `BTC/USD` is kept, which is how FTX expects them.
+#[derive(Clone, Copy, Debug)]
+pub struct MarketParam<'a>(pub &'a str);
+
+impl MarketParam<'_> {
+    /// Fail if a part between slashes is `.` or `..`, see [`PathParam::validate`].
+    pub fn validate(&self) -> Result<()> {
+        self.0
+            .split('/')
+            .try_for_each(|part| PathParam(part).validate())
+    }
+}
+
+impl fmt::Display for MarketParam<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, part) in self.0.split('/').enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            PathParam(part).fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Requests for items in a `start_time`/`end_time` window that FTX returns
+/// one capped page at a time, see [`FtxClient::paginate`](crate::FtxClient::paginate).
+pub trait Paginated: Request {
+    type Item;
+
+    /// Largest page the endpoint returns, requested when `limit` is not set.
+    const MAX_LIMIT: u32;
+
+    fn limit(&self) -> Option<u32>;
+
+    fn set_limit(&mut self, limit: u32);
+
+    /// Unix timestamp of an item, in seconds with fractions.
+    fn item_time(item: &Self::Item) -> f64;
+
+    /// Identifier used to drop items repeated on page boundaries.
+    fn item_id(item: &Self::Item) -> u64;
+}
+
+fn unix_time(time: &DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 + f64::from(time.timestamp_subsec_nanos()) / 1e9
+}
+
+/// Current time of the exchange server
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct ServerTime;
+
+impl Request for ServerTime {
+    type Response = DateTime<Utc>;
+
+    const METHOD: Method = Method::GET;
+    const NEEDS_AUTH: bool = false;
+
+    fn render_endpoint(&self) -> String {
+        "/time".into()
+    }
 }
 
 /// Obtain a list of all subaccounts
@@ -94,7 +243,11 @@ impl<'a> Request for SubaccountBalances<'a> {
     const NEEDS_AUTH: bool = true;
 
     fn render_endpoint(&self) -> String {
-        format!("/subaccounts/{}/balances", self.nickname)
+        format!("/subaccounts/{}/balances", PathParam(self.nickname))
+    }
+
+    fn validate(&self) -> Result<()> {
+        PathParam(self.nickname).validate()
     }
 }
 
@@ -127,6 +280,7 @@ impl Request for Markets {
 
     const METHOD: Method = Method::GET;
     const NEEDS_AUTH: bool = false;
+    const CLASS: RequestClass = RequestClass::MarketData;
 
     fn render_endpoint(&self) -> String {
         "/markets".into()
@@ -144,9 +298,14 @@ impl<'a> Request for Market<'a> {
 
     const METHOD: Method = Method::GET;
     const NEEDS_AUTH: bool = false;
+    const CLASS: RequestClass = RequestClass::MarketData;
 
     fn render_endpoint(&self) -> String {
-        format!("/markets/{}", self.market_name)
+        format!("/markets/{}", MarketParam(self.market_name))
+    }
+
+    fn validate(&self) -> Result<()> {
+        MarketParam(self.market_name).validate()
     }
 }
 
@@ -162,9 +321,14 @@ impl<'a> Request for Orderbook<'a> {
 
     const METHOD: Method = Method::GET;
     const NEEDS_AUTH: bool = false;
+    const CLASS: RequestClass = RequestClass::MarketData;
 
     fn render_endpoint(&self) -> String {
-        format!("/markets/{}/orderbook", self.market_name)
+        format!("/markets/{}/orderbook", MarketParam(self.market_name))
+    }
+
+    fn validate(&self) -> Result<()> {
+        MarketParam(self.market_name).validate()
     }
 }
 
@@ -178,14 +342,72 @@ pub struct Trades<'a> {
     pub end_time: Option<i64>,
 }
 
+impl<'a> Trades<'a> {
+    pub fn new(market_name: &'a str) -> Self {
+        Self {
+            market_name,
+            limit: None,
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    /// At most 100
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn end_time(mut self, end_time: i64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+}
+
 impl<'a> Request for Trades<'a> {
     type Response = Vec<model::Trade>;
 
     const METHOD: Method = Method::GET;
     const NEEDS_AUTH: bool = false;
+    const CLASS: RequestClass = RequestClass::MarketData;
 
     fn render_endpoint(&self) -> String {
-        format!("/markets/{}/trades", self.market_name)
+        format!("/markets/{}/trades", MarketParam(self.market_name))
+    }
+
+    fn validate(&self) -> Result<()> {
+        MarketParam(self.market_name).validate()?;
+        check_limit(self.limit, Self::MAX_LIMIT)?;
+        check_time_window(self.start_time, self.end_time)
+    }
+}
+
+impl<'a> Paginated for Trades<'a> {
+    type Item = model::Trade;
+
+    const MAX_LIMIT: u32 = 100;
+
+    fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn item_time(item: &Self::Item) -> f64 {
+        unix_time(&item.time)
+    }
+
+    fn item_id(item: &Self::Item) -> u64 {
+        item.id
     }
 }
 
@@ -200,14 +422,73 @@ pub struct HistoricalPrices<'a> {
     pub end_time: Option<i64>,
 }
 
+impl<'a> HistoricalPrices<'a> {
+    pub fn new(market_name: &'a str, resolution: model::TimeResolution) -> Self {
+        Self {
+            market_name,
+            resolution,
+            limit: None,
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    /// At most 5000
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn end_time(mut self, end_time: i64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+}
+
 impl<'a> Request for HistoricalPrices<'a> {
     type Response = Vec<model::HistoricalPrice>;
 
     const METHOD: Method = Method::GET;
     const NEEDS_AUTH: bool = false;
+    const CLASS: RequestClass = RequestClass::MarketData;
 
     fn render_endpoint(&self) -> String {
-        format!("/markets/{}/candles", self.market_name)
+        format!("/markets/{}/candles", MarketParam(self.market_name))
+    }
+
+    fn validate(&self) -> Result<()> {
+        MarketParam(self.market_name).validate()?;
+        check_limit(self.limit, Self::MAX_LIMIT)?;
+        check_time_window(self.start_time, self.end_time)
+    }
+}
+
+impl<'a> Paginated for HistoricalPrices<'a> {
+    type Item = model::HistoricalPrice;
+
+    const MAX_LIMIT: u32 = 5000;
+
+    fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn item_time(item: &Self::Item) -> f64 {
+        unix_time(&item.start_time)
+    }
+
+    fn item_id(item: &Self::Item) -> u64 {
+        item.start_time.timestamp() as u64
     }
 }
 
@@ -283,7 +564,11 @@ impl<'a> Request for DepositAddress<'a> {
     const NEEDS_AUTH: bool = true;
 
     fn render_endpoint(&self) -> String {
-        format!("/wallet/deposit_address/{}", self.coin)
+        format!("/wallet/deposit_address/{}", PathParam(self.coin))
+    }
+
+    fn validate(&self) -> Result<()> {
+        PathParam(self.coin).validate()
     }
 }
 
@@ -304,6 +589,33 @@ impl Request for DepositHistory {
     fn render_endpoint(&self) -> String {
         "/wallet/deposits".into()
     }
+
+    fn validate(&self) -> Result<()> {
+        check_limit(self.limit, Self::MAX_LIMIT)?;
+        check_time_window(self.start_time, self.end_time)
+    }
+}
+
+impl Paginated for DepositHistory {
+    type Item = model::TransactionHistoryEntry;
+
+    const MAX_LIMIT: u32 = 100;
+
+    fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn item_time(item: &Self::Item) -> f64 {
+        unix_time(&item.time)
+    }
+
+    fn item_id(item: &Self::Item) -> u64 {
+        item.id
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -323,6 +635,33 @@ impl Request for WithdrawalHistory {
     fn render_endpoint(&self) -> String {
         "/wallet/withdrawals".into()
     }
+
+    fn validate(&self) -> Result<()> {
+        check_limit(self.limit, Self::MAX_LIMIT)?;
+        check_time_window(self.start_time, self.end_time)
+    }
+}
+
+impl Paginated for WithdrawalHistory {
+    type Item = model::TransactionHistoryEntry;
+
+    const MAX_LIMIT: u32 = 100;
+
+    fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn item_time(item: &Self::Item) -> f64 {
+        unix_time(&item.time)
+    }
+
+    fn item_id(item: &Self::Item) -> u64 {
+        item.id
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -341,7 +680,7 @@ impl<'a> Request for OpenOrders<'a> {
     }
 }
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, Default)]
 pub struct OrderHistory<'a> {
     pub market: Option<&'a str>,
     // unix timestamps of start and end times
@@ -351,6 +690,35 @@ pub struct OrderHistory<'a> {
     pub limit: Option<u32>,
 }
 
+impl<'a> OrderHistory<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn market(mut self, market: &'a str) -> Self {
+        self.market = Some(market);
+        self
+    }
+
+    /// At most 100
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn end_time(mut self, end_time: i64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+}
+
 impl<'a> Request for OrderHistory<'a> {
     type Response = Vec<model::Order>;
 
@@ -360,6 +728,33 @@ impl<'a> Request for OrderHistory<'a> {
     fn render_endpoint(&self) -> String {
         "/orders/history".into()
     }
+
+    fn validate(&self) -> Result<()> {
+        check_limit(self.limit, Self::MAX_LIMIT)?;
+        check_time_window(self.start_time, self.end_time)
+    }
+}
+
+impl<'a> Paginated for OrderHistory<'a> {
+    type Item = model::Order;
+
+    const MAX_LIMIT: u32 = 100;
+
+    fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn item_time(item: &Self::Item) -> f64 {
+        unix_time(&item.created_at)
+    }
+
+    fn item_id(item: &Self::Item) -> u64 {
+        item.id
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -378,7 +773,7 @@ impl<'a> Request for OpenTriggerOrders<'a> {
     }
 }
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, Default)]
 pub struct TriggerOrderHistory<'a> {
     pub market: Option<&'a str>,
     pub start_time: Option<i64>,
@@ -391,6 +786,50 @@ pub struct TriggerOrderHistory<'a> {
     pub type_: Option<model::TriggerOrderType>,
 }
 
+impl<'a> TriggerOrderHistory<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn market(mut self, market: &'a str) -> Self {
+        self.market = Some(market);
+        self
+    }
+
+    pub fn side(mut self, side: model::OrderSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    pub fn order_type(mut self, order_type: model::OrderType) -> Self {
+        self.order_type = Some(order_type);
+        self
+    }
+
+    pub fn trigger_type(mut self, trigger_type: model::TriggerOrderType) -> Self {
+        self.type_ = Some(trigger_type);
+        self
+    }
+
+    /// At most 100
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Unix timestamp in seconds
+    pub fn end_time(mut self, end_time: i64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+}
+
 impl<'a> Request for TriggerOrderHistory<'a> {
     type Response = Vec<model::TriggerOrder>;
 
@@ -400,6 +839,33 @@ impl<'a> Request for TriggerOrderHistory<'a> {
     fn render_endpoint(&self) -> String {
         "/conditional_orders/history".into()
     }
+
+    fn validate(&self) -> Result<()> {
+        check_limit(self.limit, Self::MAX_LIMIT)?;
+        check_time_window(self.start_time, self.end_time)
+    }
+}
+
+impl<'a> Paginated for TriggerOrderHistory<'a> {
+    type Item = model::TriggerOrder;
+
+    const MAX_LIMIT: u32 = 100;
+
+    fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn item_time(item: &Self::Item) -> f64 {
+        unix_time(&item.created_at)
+    }
+
+    fn item_id(item: &Self::Item) -> u64 {
+        item.id
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -440,15 +906,98 @@ pub struct PlaceOrder<'a> {
     pub client_id: Option<&'a str>,
 }
 
+impl<'a> PlaceOrder<'a> {
+    /// Limit order for `size` at `price`, e.g.
+    /// `PlaceOrder::limit("BTC-PERP", OrderSide::Buy, price, size).post_only()`.
+    pub fn limit(market: &'a str, side: model::OrderSide, price: Decimal, size: Decimal) -> Self {
+        Self::new(market, side, PlaceOrderTypeInfo::Limit { price }, size)
+    }
+
+    pub fn market(market: &'a str, side: model::OrderSide, size: Decimal) -> Self {
+        Self::new(market, side, PlaceOrderTypeInfo::Market, size)
+    }
+
+    fn new(
+        market: &'a str,
+        side: model::OrderSide,
+        type_: PlaceOrderTypeInfo,
+        size: Decimal,
+    ) -> Self {
+        Self {
+            market,
+            side,
+            type_,
+            size,
+            reduce_only: false,
+            ioc: false,
+            post_only: false,
+            client_id: None,
+        }
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Immediate or cancel
+    pub fn ioc(mut self) -> Self {
+        self.ioc = true;
+        self
+    }
+
+    pub fn post_only(mut self) -> Self {
+        self.post_only = true;
+        self
+    }
+
+    /// Client order id, not named `client_id` to leave [`Request::client_id`] unshadowed
+    pub fn with_client_id(mut self, client_id: &'a str) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+}
+
 impl<'a> Request for PlaceOrder<'a> {
     type Response = model::Order;
 
     const METHOD: Method = Method::POST;
     const NEEDS_AUTH: bool = true;
+    const CLASS: RequestClass = RequestClass::Order;
 
     fn render_endpoint(&self) -> String {
         "/orders".into()
     }
+
+    /// `None` for market and IOC orders, which are closed once placed
+    fn client_id(&self) -> Option<&str> {
+        match self.type_ {
+            PlaceOrderTypeInfo::Limit { .. } if !self.ioc => self.client_id,
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.market.is_empty() {
+            return invalid("market is empty");
+        }
+        if self.size <= Decimal::ZERO {
+            return invalid(format!("size {} is not positive", self.size));
+        }
+        match self.type_ {
+            PlaceOrderTypeInfo::Limit { price } if price <= Decimal::ZERO => {
+                return invalid(format!("price {} is not positive", price))
+            }
+            PlaceOrderTypeInfo::Market if self.post_only => {
+                return invalid("market orders can't be post only")
+            }
+            _ => {}
+        }
+        if self.post_only && self.ioc {
+            return invalid("an order can't be both post only and immediate or cancel");
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -458,6 +1007,15 @@ pub enum OrderRequestId<'a> {
     Order(u64),
 }
 
+impl OrderRequestId<'_> {
+    fn validate(&self) -> Result<()> {
+        match self {
+            OrderRequestId::Client(client_id) => PathParam(client_id).validate(),
+            OrderRequestId::Order(_) => Ok(()),
+        }
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct OrderStatus<'a> {
     #[serde(skip)]
@@ -472,10 +1030,14 @@ impl<'a> Request for OrderStatus<'a> {
 
     fn render_endpoint(&self) -> String {
         match &self.order_request_id {
-            OrderRequestId::Client(s) => format!("/orders/by_client_id/{}", s),
+            OrderRequestId::Client(s) => format!("/orders/by_client_id/{}", PathParam(s)),
             OrderRequestId::Order(id) => format!("/orders/{}", id),
         }
     }
+
+    fn validate(&self) -> Result<()> {
+        self.order_request_id.validate()
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -488,18 +1050,66 @@ pub struct ModifyOrder<'a> {
     pub client_id: Option<&'a str>,
 }
 
+impl<'a> ModifyOrder<'a> {
+    pub fn new(order_request_id: OrderRequestId<'a>) -> Self {
+        Self {
+            order_request_id,
+            price: None,
+            size: None,
+            client_id: None,
+        }
+    }
+
+    pub fn price(mut self, price: Decimal) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn size(mut self, size: Decimal) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// New client id of the modified order
+    pub fn with_client_id(mut self, client_id: &'a str) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+}
+
 impl<'a> Request for ModifyOrder<'a> {
     type Response = model::Order;
 
     const METHOD: Method = Method::POST;
     const NEEDS_AUTH: bool = true;
+    const CLASS: RequestClass = RequestClass::Order;
 
     fn render_endpoint(&self) -> String {
         match &self.order_request_id {
-            OrderRequestId::Client(s) => format!("/orders/by_client_id/{}/modify", s),
+            OrderRequestId::Client(s) => {
+                format!("/orders/by_client_id/{}/modify", PathParam(s))
+            }
             OrderRequestId::Order(id) => format!("/orders/{}/modify", id),
         }
     }
+
+    fn client_id(&self) -> Option<&str> {
+        self.client_id
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.order_request_id.validate()?;
+        match (self.price, self.size) {
+            (None, None) => invalid("either price or size must be modified"),
+            (Some(price), _) if price <= Decimal::ZERO => {
+                invalid(format!("price {} is not positive", price))
+            }
+            (_, Some(size)) if size <= Decimal::ZERO => {
+                invalid(format!("size {} is not positive", size))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -513,13 +1123,18 @@ impl<'a> Request for CancelOrder<'a> {
 
     const METHOD: Method = Method::DELETE;
     const NEEDS_AUTH: bool = true;
+    const CLASS: RequestClass = RequestClass::Order;
 
     fn render_endpoint(&self) -> String {
         match &self.order_request_id {
-            OrderRequestId::Client(s) => format!("/orders/by_client_id/{}", s),
+            OrderRequestId::Client(s) => format!("/orders/by_client_id/{}", PathParam(s)),
             OrderRequestId::Order(id) => format!("/orders/{}", id),
         }
     }
+
+    fn validate(&self) -> Result<()> {
+        self.order_request_id.validate()
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -533,6 +1148,7 @@ impl Request for CancelTriggerOrder {
 
     const METHOD: Method = Method::DELETE;
     const NEEDS_AUTH: bool = true;
+    const CLASS: RequestClass = RequestClass::Order;
 
     fn render_endpoint(&self) -> String {
         format!("/conditional_orders/{}", self.trigger_order_id)
@@ -553,8 +1169,139 @@ impl<'a> Request for CancelAllOrders<'a> {
 
     const METHOD: Method = Method::DELETE;
     const NEEDS_AUTH: bool = true;
+    const CLASS: RequestClass = RequestClass::Order;
 
     fn render_endpoint(&self) -> String {
         "/orders".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CancelOrder, DepositAddress, DepositHistory, HistoricalPrices, Market, MarketParam,
+        ModifyOrder, OrderHistory, OrderRequestId, OrderStatus, PathParam, PlaceOrder, Request,
+        SubaccountBalances, Trades,
+    };
+    use crate::{
+        model::{OrderSide, TimeResolution},
+        FtxError,
+    };
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn path_params_are_encoded() {
+        let market = Market {
+            market_name: "BTC/USD",
+        };
+        assert_eq!("/markets/BTC/USD", market.render_endpoint());
+        let market = Market {
+            market_name: "BTC-PERP?x#",
+        };
+        assert_eq!("/markets/BTC-PERP%3Fx%23", market.render_endpoint());
+
+        let balances = SubaccountBalances {
+            nickname: "my bot/1",
+        };
+        assert_eq!(
+            "/subaccounts/my%20bot%2F1/balances",
+            balances.render_endpoint()
+        );
+
+        let address = DepositAddress {
+            coin: "../USD",
+            method: None,
+        };
+        assert_eq!(
+            "/wallet/deposit_address/..%2FUSD",
+            address.render_endpoint()
+        );
+
+        let cancel = CancelOrder {
+            order_request_id: OrderRequestId::Client("a/b?c#d%e"),
+        };
+        assert_eq!(
+            "/orders/by_client_id/a%2Fb%3Fc%23d%25e",
+            cancel.render_endpoint()
+        );
+        let modify = ModifyOrder {
+            order_request_id: OrderRequestId::Client("ünï"),
+            price: None,
+            size: None,
+            client_id: None,
+        };
+        assert_eq!(
+            "/orders/by_client_id/%C3%BCn%C3%AF/modify",
+            modify.render_endpoint()
+        );
+
+        // dot segments would be resolved by the url parser, changing the endpoint
+        let cancel = CancelOrder {
+            order_request_id: OrderRequestId::Client(".."),
+        };
+        assert!(matches!(
+            cancel.validate(),
+            Err(FtxError::InvalidRequest(_))
+        ));
+        let status = OrderStatus {
+            order_request_id: OrderRequestId::Client("."),
+        };
+        assert!(status.validate().is_err());
+        let market = Market {
+            market_name: "BTC/..",
+        };
+        assert!(market.validate().is_err());
+        let balances = SubaccountBalances { nickname: "." };
+        assert!(balances.validate().is_err());
+        assert!(PathParam("...").validate().is_ok());
+        assert!(MarketParam("BTC/USD").validate().is_ok());
+    }
+
+    #[test]
+    fn builders_are_validated() {
+        let price = Decimal::from(40000);
+        let size: Decimal = "0.1".parse().unwrap();
+        let order = PlaceOrder::limit("BTC-PERP", OrderSide::Buy, price, size)
+            .post_only()
+            .with_client_id("grid/1");
+        assert!(order.post_only && !order.ioc);
+        assert_eq!(Some("grid/1"), order.client_id());
+        order.validate().unwrap();
+
+        let invalid = [
+            PlaceOrder::limit("BTC-PERP", OrderSide::Buy, price, size)
+                .post_only()
+                .ioc(),
+            PlaceOrder::market("BTC-PERP", OrderSide::Sell, size).post_only(),
+            PlaceOrder::limit("BTC-PERP", OrderSide::Buy, Decimal::ZERO, size),
+            PlaceOrder::market("BTC-PERP", OrderSide::Sell, Decimal::ZERO),
+        ];
+        for order in &invalid {
+            assert!(matches!(order.validate(), Err(FtxError::InvalidRequest(_))));
+        }
+
+        assert!(Trades::new("BTC/USD").with_limit(100).validate().is_ok());
+        assert!(Trades::new("BTC/USD").with_limit(101).validate().is_err());
+        assert!(OrderHistory::new().with_limit(0).validate().is_err());
+        let deposits = DepositHistory {
+            limit: Some(101),
+            start_time: None,
+            end_time: None,
+        };
+        assert!(deposits.validate().is_err());
+        assert!(OrderHistory::new()
+            .start_time(1_600_000_100)
+            .end_time(1_600_000_000)
+            .validate()
+            .is_err());
+        let candles = HistoricalPrices::new("BTC-PERP", TimeResolution::T1m).with_limit(1500);
+        assert!(candles.validate().is_ok());
+        assert!(ModifyOrder::new(OrderRequestId::Order(1))
+            .validate()
+            .is_err());
+        assert!(ModifyOrder::new(OrderRequestId::Order(1))
+            .size(size)
+            .validate()
+            .is_ok());
+    }
+}
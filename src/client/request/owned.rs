@@ -0,0 +1,745 @@
+//! Owned counterparts of the requests borrowing strings, for queueing them in
+//! channels or keeping them across tasks. They are sent exactly like the borrowed forms,
+//! which they convert to and from with [`From`].
+
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde::{Serialize, Serializer};
+
+use super::{Paginated, PlaceOrderTypeInfo, Request, RequestClass};
+use crate::model;
+
+/// Implement `Serialize` and `Request` by converting to the borrowed request.
+macro_rules! owned_request {
+    ($owned:ident => $borrowed:ident $(, client_id: $client_id:ident)?) => {
+        impl Serialize for $owned {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::$borrowed::from(self).serialize(serializer)
+            }
+        }
+
+        impl Request for $owned {
+            type Response = <super::$borrowed<'static> as Request>::Response;
+
+            const METHOD: Method = <super::$borrowed<'static> as Request>::METHOD;
+            const NEEDS_AUTH: bool = <super::$borrowed<'static> as Request>::NEEDS_AUTH;
+            const CLASS: RequestClass = <super::$borrowed<'static> as Request>::CLASS;
+
+            fn render_endpoint(&self) -> String {
+                super::$borrowed::from(self).render_endpoint()
+            }
+
+            fn validate(&self) -> crate::error::Result<()> {
+                super::$borrowed::from(self).validate()
+            }
+
+            $(
+                fn client_id(&self) -> Option<&str> {
+                    // the borrowed form decides whether the client id allows retries
+                    if super::$borrowed::from(self).client_id().is_some() {
+                        self.$client_id.as_deref()
+                    } else {
+                        None
+                    }
+                }
+            )?
+        }
+    };
+}
+
+/// Implement `Paginated` with the page size and items of the borrowed request.
+macro_rules! owned_paginated {
+    ($owned:ident => $borrowed:ident) => {
+        impl Paginated for $owned {
+            type Item = <super::$borrowed<'static> as Paginated>::Item;
+
+            const MAX_LIMIT: u32 = <super::$borrowed<'static> as Paginated>::MAX_LIMIT;
+
+            fn limit(&self) -> Option<u32> {
+                self.limit
+            }
+
+            fn set_limit(&mut self, limit: u32) {
+                self.limit = Some(limit);
+            }
+
+            fn item_time(item: &Self::Item) -> f64 {
+                <super::$borrowed<'static> as Paginated>::item_time(item)
+            }
+
+            fn item_id(item: &Self::Item) -> u64 {
+                <super::$borrowed<'static> as Paginated>::item_id(item)
+            }
+        }
+    };
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderRequestId {
+    Client(String),
+    Order(u64),
+}
+
+impl From<super::OrderRequestId<'_>> for OrderRequestId {
+    fn from(id: super::OrderRequestId) -> Self {
+        match id {
+            super::OrderRequestId::Client(client_id) => OrderRequestId::Client(client_id.into()),
+            super::OrderRequestId::Order(id) => OrderRequestId::Order(id),
+        }
+    }
+}
+
+impl<'a> From<&'a OrderRequestId> for super::OrderRequestId<'a> {
+    fn from(id: &'a OrderRequestId) -> Self {
+        match id {
+            OrderRequestId::Client(client_id) => super::OrderRequestId::Client(client_id),
+            OrderRequestId::Order(id) => super::OrderRequestId::Order(*id),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateSubaccount {
+    pub nickname: String,
+}
+
+owned_request!(CreateSubaccount => CreateSubaccount);
+
+impl From<super::CreateSubaccount<'_>> for CreateSubaccount {
+    fn from(create: super::CreateSubaccount) -> Self {
+        Self {
+            nickname: create.nickname.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a CreateSubaccount> for super::CreateSubaccount<'a> {
+    fn from(create: &'a CreateSubaccount) -> Self {
+        Self {
+            nickname: &create.nickname,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SubaccountUpdateName {
+    pub nickname: String,
+    pub new_nickname: String,
+}
+
+owned_request!(SubaccountUpdateName => SubaccountUpdateName);
+
+impl From<super::SubaccountUpdateName<'_>> for SubaccountUpdateName {
+    fn from(update: super::SubaccountUpdateName) -> Self {
+        Self {
+            nickname: update.nickname.into(),
+            new_nickname: update.new_nickname.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a SubaccountUpdateName> for super::SubaccountUpdateName<'a> {
+    fn from(update: &'a SubaccountUpdateName) -> Self {
+        Self {
+            nickname: &update.nickname,
+            new_nickname: &update.new_nickname,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeleteSubaccount {
+    pub nickname: String,
+}
+
+owned_request!(DeleteSubaccount => DeleteSubaccount);
+
+impl From<super::DeleteSubaccount<'_>> for DeleteSubaccount {
+    fn from(delete: super::DeleteSubaccount) -> Self {
+        Self {
+            nickname: delete.nickname.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a DeleteSubaccount> for super::DeleteSubaccount<'a> {
+    fn from(delete: &'a DeleteSubaccount) -> Self {
+        Self {
+            nickname: &delete.nickname,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SubaccountBalances {
+    pub nickname: String,
+}
+
+owned_request!(SubaccountBalances => SubaccountBalances);
+
+impl From<super::SubaccountBalances<'_>> for SubaccountBalances {
+    fn from(balances: super::SubaccountBalances) -> Self {
+        Self {
+            nickname: balances.nickname.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a SubaccountBalances> for super::SubaccountBalances<'a> {
+    fn from(balances: &'a SubaccountBalances) -> Self {
+        Self {
+            nickname: &balances.nickname,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SubaccountTransfer {
+    pub coin: String,
+    pub size: Decimal,
+    pub source: String,
+    pub destination: String,
+}
+
+owned_request!(SubaccountTransfer => SubaccountTransfer);
+
+impl From<super::SubaccountTransfer<'_>> for SubaccountTransfer {
+    fn from(transfer: super::SubaccountTransfer) -> Self {
+        Self {
+            coin: transfer.coin.into(),
+            size: transfer.size,
+            source: transfer.source.into(),
+            destination: transfer.destination.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a SubaccountTransfer> for super::SubaccountTransfer<'a> {
+    fn from(transfer: &'a SubaccountTransfer) -> Self {
+        Self {
+            coin: &transfer.coin,
+            size: transfer.size,
+            source: &transfer.source,
+            destination: &transfer.destination,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Market {
+    pub market_name: String,
+}
+
+owned_request!(Market => Market);
+
+impl From<super::Market<'_>> for Market {
+    fn from(market: super::Market) -> Self {
+        Self {
+            market_name: market.market_name.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a Market> for super::Market<'a> {
+    fn from(market: &'a Market) -> Self {
+        Self {
+            market_name: &market.market_name,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Orderbook {
+    pub market_name: String,
+    pub depth: Option<u32>,
+}
+
+owned_request!(Orderbook => Orderbook);
+
+impl From<super::Orderbook<'_>> for Orderbook {
+    fn from(orderbook: super::Orderbook) -> Self {
+        Self {
+            market_name: orderbook.market_name.into(),
+            depth: orderbook.depth,
+        }
+    }
+}
+
+impl<'a> From<&'a Orderbook> for super::Orderbook<'a> {
+    fn from(orderbook: &'a Orderbook) -> Self {
+        Self {
+            market_name: &orderbook.market_name,
+            depth: orderbook.depth,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Trades {
+    pub market_name: String,
+    pub limit: Option<u32>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+}
+
+owned_request!(Trades => Trades);
+owned_paginated!(Trades => Trades);
+
+impl From<super::Trades<'_>> for Trades {
+    fn from(trades: super::Trades) -> Self {
+        Self {
+            market_name: trades.market_name.into(),
+            limit: trades.limit,
+            start_time: trades.start_time,
+            end_time: trades.end_time,
+        }
+    }
+}
+
+impl<'a> From<&'a Trades> for super::Trades<'a> {
+    fn from(trades: &'a Trades) -> Self {
+        Self {
+            market_name: &trades.market_name,
+            limit: trades.limit,
+            start_time: trades.start_time,
+            end_time: trades.end_time,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoricalPrices {
+    pub market_name: String,
+    pub resolution: model::TimeResolution,
+    pub limit: Option<u32>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+}
+
+owned_request!(HistoricalPrices => HistoricalPrices);
+owned_paginated!(HistoricalPrices => HistoricalPrices);
+
+impl From<super::HistoricalPrices<'_>> for HistoricalPrices {
+    fn from(prices: super::HistoricalPrices) -> Self {
+        Self {
+            market_name: prices.market_name.into(),
+            resolution: prices.resolution,
+            limit: prices.limit,
+            start_time: prices.start_time,
+            end_time: prices.end_time,
+        }
+    }
+}
+
+impl<'a> From<&'a HistoricalPrices> for super::HistoricalPrices<'a> {
+    fn from(prices: &'a HistoricalPrices) -> Self {
+        Self {
+            market_name: &prices.market_name,
+            resolution: prices.resolution,
+            limit: prices.limit,
+            start_time: prices.start_time,
+            end_time: prices.end_time,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DepositAddress {
+    pub coin: String,
+    pub method: Option<String>,
+}
+
+owned_request!(DepositAddress => DepositAddress);
+
+impl From<super::DepositAddress<'_>> for DepositAddress {
+    fn from(address: super::DepositAddress) -> Self {
+        Self {
+            coin: address.coin.into(),
+            method: address.method.map(Into::into),
+        }
+    }
+}
+
+impl<'a> From<&'a DepositAddress> for super::DepositAddress<'a> {
+    fn from(address: &'a DepositAddress) -> Self {
+        Self {
+            coin: &address.coin,
+            method: address.method.as_deref(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OpenOrders {
+    pub market: Option<String>,
+}
+
+owned_request!(OpenOrders => OpenOrders);
+
+impl From<super::OpenOrders<'_>> for OpenOrders {
+    fn from(orders: super::OpenOrders) -> Self {
+        Self {
+            market: orders.market.map(Into::into),
+        }
+    }
+}
+
+impl<'a> From<&'a OpenOrders> for super::OpenOrders<'a> {
+    fn from(orders: &'a OpenOrders) -> Self {
+        Self {
+            market: orders.market.as_deref(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OrderHistory {
+    pub market: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+owned_request!(OrderHistory => OrderHistory);
+owned_paginated!(OrderHistory => OrderHistory);
+
+impl From<super::OrderHistory<'_>> for OrderHistory {
+    fn from(history: super::OrderHistory) -> Self {
+        Self {
+            market: history.market.map(Into::into),
+            start_time: history.start_time,
+            end_time: history.end_time,
+            limit: history.limit,
+        }
+    }
+}
+
+impl<'a> From<&'a OrderHistory> for super::OrderHistory<'a> {
+    fn from(history: &'a OrderHistory) -> Self {
+        Self {
+            market: history.market.as_deref(),
+            start_time: history.start_time,
+            end_time: history.end_time,
+            limit: history.limit,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OpenTriggerOrders {
+    pub market: Option<String>,
+}
+
+owned_request!(OpenTriggerOrders => OpenTriggerOrders);
+
+impl From<super::OpenTriggerOrders<'_>> for OpenTriggerOrders {
+    fn from(orders: super::OpenTriggerOrders) -> Self {
+        Self {
+            market: orders.market.map(Into::into),
+        }
+    }
+}
+
+impl<'a> From<&'a OpenTriggerOrders> for super::OpenTriggerOrders<'a> {
+    fn from(orders: &'a OpenTriggerOrders) -> Self {
+        Self {
+            market: orders.market.as_deref(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerOrderHistory {
+    pub market: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub side: Option<model::OrderSide>,
+    pub order_type: Option<model::OrderType>,
+    pub type_: Option<model::TriggerOrderType>,
+}
+
+owned_request!(TriggerOrderHistory => TriggerOrderHistory);
+owned_paginated!(TriggerOrderHistory => TriggerOrderHistory);
+
+impl From<super::TriggerOrderHistory<'_>> for TriggerOrderHistory {
+    fn from(history: super::TriggerOrderHistory) -> Self {
+        Self {
+            market: history.market.map(Into::into),
+            start_time: history.start_time,
+            end_time: history.end_time,
+            limit: history.limit,
+            side: history.side,
+            order_type: history.order_type,
+            type_: history.type_,
+        }
+    }
+}
+
+impl<'a> From<&'a TriggerOrderHistory> for super::TriggerOrderHistory<'a> {
+    fn from(history: &'a TriggerOrderHistory) -> Self {
+        Self {
+            market: history.market.as_deref(),
+            start_time: history.start_time,
+            end_time: history.end_time,
+            limit: history.limit,
+            side: history.side.clone(),
+            order_type: history.order_type,
+            type_: history.type_,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PlaceOrder {
+    pub market: String,
+    pub side: model::OrderSide,
+    pub type_: PlaceOrderTypeInfo,
+    pub size: Decimal,
+    pub reduce_only: bool,
+    pub ioc: bool,
+    pub post_only: bool,
+    pub client_id: Option<String>,
+}
+
+owned_request!(PlaceOrder => PlaceOrder, client_id: client_id);
+
+impl From<super::PlaceOrder<'_>> for PlaceOrder {
+    fn from(order: super::PlaceOrder) -> Self {
+        Self {
+            market: order.market.into(),
+            side: order.side,
+            type_: order.type_,
+            size: order.size,
+            reduce_only: order.reduce_only,
+            ioc: order.ioc,
+            post_only: order.post_only,
+            client_id: order.client_id.map(Into::into),
+        }
+    }
+}
+
+impl<'a> From<&'a PlaceOrder> for super::PlaceOrder<'a> {
+    fn from(order: &'a PlaceOrder) -> Self {
+        Self {
+            market: &order.market,
+            side: order.side.clone(),
+            type_: order.type_,
+            size: order.size,
+            reduce_only: order.reduce_only,
+            ioc: order.ioc,
+            post_only: order.post_only,
+            client_id: order.client_id.as_deref(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OrderStatus {
+    pub order_request_id: OrderRequestId,
+}
+
+owned_request!(OrderStatus => OrderStatus);
+
+impl From<super::OrderStatus<'_>> for OrderStatus {
+    fn from(status: super::OrderStatus) -> Self {
+        Self {
+            order_request_id: status.order_request_id.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a OrderStatus> for super::OrderStatus<'a> {
+    fn from(status: &'a OrderStatus) -> Self {
+        Self {
+            order_request_id: (&status.order_request_id).into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModifyOrder {
+    pub order_request_id: OrderRequestId,
+    pub price: Option<Decimal>,
+    pub size: Option<Decimal>,
+    pub client_id: Option<String>,
+}
+
+owned_request!(ModifyOrder => ModifyOrder, client_id: client_id);
+
+impl From<super::ModifyOrder<'_>> for ModifyOrder {
+    fn from(modify: super::ModifyOrder) -> Self {
+        Self {
+            order_request_id: modify.order_request_id.into(),
+            price: modify.price,
+            size: modify.size,
+            client_id: modify.client_id.map(Into::into),
+        }
+    }
+}
+
+impl<'a> From<&'a ModifyOrder> for super::ModifyOrder<'a> {
+    fn from(modify: &'a ModifyOrder) -> Self {
+        Self {
+            order_request_id: (&modify.order_request_id).into(),
+            price: modify.price,
+            size: modify.size,
+            client_id: modify.client_id.as_deref(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CancelOrder {
+    pub order_request_id: OrderRequestId,
+}
+
+owned_request!(CancelOrder => CancelOrder);
+
+impl From<super::CancelOrder<'_>> for CancelOrder {
+    fn from(cancel: super::CancelOrder) -> Self {
+        Self {
+            order_request_id: cancel.order_request_id.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a CancelOrder> for super::CancelOrder<'a> {
+    fn from(cancel: &'a CancelOrder) -> Self {
+        Self {
+            order_request_id: (&cancel.order_request_id).into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CancelAllOrders {
+    pub market: Option<String>,
+    pub trigger_orders_only: bool,
+    pub limit_orders_only: bool,
+}
+
+owned_request!(CancelAllOrders => CancelAllOrders);
+
+impl From<super::CancelAllOrders<'_>> for CancelAllOrders {
+    fn from(cancel: super::CancelAllOrders) -> Self {
+        Self {
+            market: cancel.market.map(Into::into),
+            trigger_orders_only: cancel.trigger_orders_only,
+            limit_orders_only: cancel.limit_orders_only,
+        }
+    }
+}
+
+impl<'a> From<&'a CancelAllOrders> for super::CancelAllOrders<'a> {
+    fn from(cancel: &'a CancelAllOrders) -> Self {
+        Self {
+            market: cancel.market.as_deref(),
+            trigger_orders_only: cancel.trigger_orders_only,
+            limit_orders_only: cancel.limit_orders_only,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CancelOrder, ModifyOrder, OrderHistory, OrderRequestId, PlaceOrder, SubaccountBalances,
+        TriggerOrderHistory,
+    };
+    use crate::{
+        model::{OrderSide, TriggerOrderType},
+        request::{self, Paginated, PlaceOrderTypeInfo, Request},
+    };
+
+    fn assert_static_send<T: 'static + Send>(_: &T) {}
+
+    #[test]
+    fn owned_requests_match_borrowed() {
+        let borrowed = request::PlaceOrder {
+            market: "BTC/USD",
+            side: OrderSide::Buy,
+            type_: PlaceOrderTypeInfo::Limit {
+                price: "40000".parse().unwrap(),
+            },
+            size: "0.1".parse().unwrap(),
+            reduce_only: false,
+            ioc: false,
+            post_only: true,
+            client_id: Some("grid/1"),
+        };
+        let owned = PlaceOrder::from(borrowed.clone());
+        assert_static_send(&owned);
+        assert_eq!(
+            serde_json::to_string(&borrowed).unwrap(),
+            serde_json::to_string(&owned).unwrap()
+        );
+        assert_eq!(Some("grid/1"), owned.client_id());
+        let ioc = PlaceOrder {
+            post_only: false,
+            ioc: true,
+            ..owned.clone()
+        };
+        assert_eq!(None, ioc.client_id());
+
+        let modify = ModifyOrder::from(request::ModifyOrder {
+            order_request_id: request::OrderRequestId::Client("grid/1"),
+            price: None,
+            size: Some("0.2".parse().unwrap()),
+            client_id: None,
+        });
+        assert_eq!(
+            OrderRequestId::Client("grid/1".into()),
+            modify.order_request_id
+        );
+        assert_eq!(
+            "/orders/by_client_id/grid%2F1/modify",
+            modify.render_endpoint()
+        );
+
+        let cancel = CancelOrder {
+            order_request_id: OrderRequestId::Order(42),
+        };
+        assert_eq!("/orders/42", cancel.render_endpoint());
+        assert_eq!(
+            "/orders/42",
+            request::CancelOrder::from(&cancel).render_endpoint()
+        );
+    }
+
+    #[test]
+    fn owned_history_requests() {
+        let borrowed = request::TriggerOrderHistory::new()
+            .market("BTC/USD")
+            .side(OrderSide::Sell)
+            .trigger_type(TriggerOrderType::Stop)
+            .with_limit(50);
+        let owned = TriggerOrderHistory::from(borrowed.clone());
+        assert_static_send(&owned);
+        assert_eq!(
+            serde_json::to_string(&borrowed).unwrap(),
+            serde_json::to_string(&owned).unwrap()
+        );
+        assert_eq!(Some(50), owned.limit());
+
+        let mut history = OrderHistory {
+            market: Some("BTC/USD".into()),
+            start_time: Some(1),
+            end_time: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(100, OrderHistory::MAX_LIMIT);
+        assert_eq!(
+            r#"{"market":"BTC/USD","start_time":1,"end_time":2,"limit":null}"#,
+            serde_json::to_string(&history).unwrap()
+        );
+        history.set_limit(0);
+        assert!(history.validate().is_err());
+
+        let balances = SubaccountBalances {
+            nickname: "bot/1".into(),
+        };
+        assert_eq!("/subaccounts/bot%2F1/balances", balances.render_endpoint());
+    }
+}
@@ -0,0 +1,167 @@
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use std::time::Duration;
+
+use super::RawResponse;
+use crate::error::{FtxError, Result};
+
+/// When and how often failed requests are retried.
+///
+/// Connection failures, timeouts, `5xx` and `429 Too Many Requests` responses
+/// are retried with exponential backoff and jitter. `GET` requests are always
+/// retried, mutating requests only when enabled with
+/// [`retry_with_client_id`](Self::retry_with_client_id) and when they carry a client id.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    retry_with_client_id: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            retry_with_client_id: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Never retry.
+    pub fn none() -> Self {
+        Self::new().max_attempts(1)
+    }
+
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Delay before the first retry, doubled for every following one.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Upper bound of the backoff. A `429` asking to wait longer than this with
+    /// `Retry-After` is returned instead of retried.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Also retry mutating requests, such as placing an order, that carry a client id.
+    ///
+    /// FTX only rejects a duplicate client id while the first order is still open, so
+    /// this is not safe for orders that can close immediately. Market and IOC orders
+    /// are never retried, but a limit order that fills completely as soon as it is
+    /// placed is placed again if the response to the first attempt was lost.
+    pub fn retry_with_client_id(mut self, enabled: bool) -> Self {
+        self.retry_with_client_id = enabled;
+        self
+    }
+
+    pub(super) fn retries_with_client_id(&self) -> bool {
+        self.retry_with_client_id
+    }
+
+    /// Delay before the next attempt, or `None` if `resp` should be returned as is.
+    pub(super) fn retry_delay(&self, attempt: u32, resp: &Result<RawResponse>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        match resp {
+            Ok(resp) if resp.status == StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = resp
+                    .headers
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .map(Duration::from_secs);
+                match retry_after {
+                    Some(delay) if delay > self.max_delay => None,
+                    Some(delay) => Some(delay),
+                    None => Some(self.backoff(attempt)),
+                }
+            }
+            Ok(resp) if resp.status.is_server_error() => Some(self.backoff(attempt)),
+            Err(FtxError::Transport(e))
+                if e.is_connect() || e.is_timeout() || e.is_request() || e.is_body() =>
+            {
+                Some(self.backoff(attempt))
+            }
+            _ => None,
+        }
+    }
+
+    /// Exponential backoff with jitter, between half and the full delay.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .checked_mul(1 << (attempt - 1).min(16))
+            .map_or(self.max_delay, |d| d.min(self.max_delay));
+        delay / 2 + delay.mul_f64(rand::thread_rng().gen_range(0.0..0.5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RawResponse, RetryPolicy};
+    use reqwest::{header::HeaderMap, StatusCode};
+    use std::time::Duration;
+
+    fn response(status: StatusCode) -> RawResponse {
+        RawResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: String::new(),
+            latency: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn retries_transient_statuses() {
+        let policy = RetryPolicy::new()
+            .max_attempts(3)
+            .base_delay(Duration::from_millis(100));
+
+        let delay = policy
+            .retry_delay(2, &Ok(response(StatusCode::BAD_GATEWAY)))
+            .unwrap();
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+
+        let mut rate_limited = response(StatusCode::TOO_MANY_REQUESTS);
+        rate_limited
+            .headers
+            .insert("retry-after", "3".parse().unwrap());
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            policy.retry_delay(1, &Ok(rate_limited))
+        );
+
+        let mut rate_limited = response(StatusCode::TOO_MANY_REQUESTS);
+        rate_limited
+            .headers
+            .insert("retry-after", "3600".parse().unwrap());
+        assert_eq!(None, policy.retry_delay(1, &Ok(rate_limited)));
+
+        assert_eq!(
+            None,
+            policy.retry_delay(1, &Ok(response(StatusCode::BAD_REQUEST)))
+        );
+        assert_eq!(
+            None,
+            policy.retry_delay(3, &Ok(response(StatusCode::BAD_GATEWAY)))
+        );
+    }
+}
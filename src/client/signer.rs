@@ -0,0 +1,87 @@
+use futures::future::{self, BoxFuture, FutureExt};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::{fmt, sync::Arc};
+
+use super::Secret;
+use crate::error::{FtxError, Result};
+
+/// Signs REST requests and websocket logins.
+///
+/// `payload` is the string FTX expects to be authenticated, e.g.
+/// `1588591511721GET/api/markets` or `1588591511721websocket_login`,
+/// and the result is the lowercase hex HMAC-SHA256 of it keyed by the API secret.
+/// Implement this to keep the secret out of the process, e.g. in an HSM.
+pub trait Signer: Send + Sync {
+    fn sign(&self, payload: &str) -> Result<String>;
+}
+
+/// Like [`Signer`], for signing services that are reached asynchronously.
+///
+/// Every [`Signer`] is also an `AsyncSigner`.
+pub trait AsyncSigner: Send + Sync {
+    fn sign<'a>(&'a self, payload: &'a str) -> BoxFuture<'a, Result<String>>;
+}
+
+impl<S: Signer> AsyncSigner for S {
+    fn sign<'a>(&'a self, payload: &'a str) -> BoxFuture<'a, Result<String>> {
+        future::ready(Signer::sign(self, payload)).boxed()
+    }
+}
+
+/// Default signer, computes HMAC-SHA256 in process with the API secret.
+#[derive(Debug, Clone)]
+pub struct HmacSigner {
+    secret: Secret,
+}
+
+impl HmacSigner {
+    pub fn new(secret: impl Into<Secret>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+}
+
+impl Signer for HmacSigner {
+    fn sign(&self, payload: &str) -> Result<String> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.expose().as_bytes())
+            .map_err(|e| FtxError::Signing(e.to_string()))?;
+        mac.update(payload.as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct SharedSigner(Arc<dyn AsyncSigner>);
+
+impl fmt::Debug for SharedSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Signer")
+    }
+}
+
+impl SharedSigner {
+    pub(super) fn new(signer: impl AsyncSigner + 'static) -> Self {
+        Self(Arc::new(signer))
+    }
+
+    pub(super) async fn sign(&self, payload: &str) -> Result<String> {
+        self.0.sign(payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HmacSigner, Signer};
+
+    #[test]
+    fn hmac_signature() {
+        // Example from the FTX REST API documentation
+        let signer = HmacSigner::new("T4lPid48QtjNxjLUFOcUZghD7CUJ7sTVsfuvQZF2");
+        assert_eq!(
+            "dbc62ec300b2624c580611858d94f2332ac636bb86eccfa1167a7777c496ee6f",
+            signer.sign("1588591511721GET/api/markets").unwrap()
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! `tracing` instrumentation of REST requests, compiled to nothing without the `tracing` feature.
+
+use std::future::Future;
+
+use super::{FtxClient, PreparedRequest, RawResponse};
+use crate::error::Result;
+#[cfg(feature = "websocket")]
+use crate::model::websocket::{WsInMessage, WsOutMessage};
+
+#[cfg(feature = "tracing")]
+impl FtxClient {
+    /// Run `request` inside an `ftx.request` span, recording its outcome and latency.
+    pub(super) async fn traced<T>(
+        &self,
+        request: &PreparedRequest,
+        future: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        use tracing::{field, Instrument};
+
+        let span = tracing::info_span!(
+            "ftx.request",
+            method = %request.method,
+            endpoint = request.url.path(),
+            subaccount = field::Empty,
+            status = field::Empty,
+            attempts = field::Empty,
+            latency_ms = field::Empty,
+            outcome = field::Empty,
+        );
+        if let Some(subaccount) = self.auth.as_ref().and_then(|a| a.subaccount.as_deref()) {
+            span.record("subaccount", subaccount);
+        }
+
+        let started = std::time::Instant::now();
+        let result = future.instrument(span.clone()).await;
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        match &result {
+            Ok(_) => {
+                span.record("outcome", "ok");
+            }
+            Err(e) => {
+                span.record("outcome", "error");
+                span.in_scope(|| tracing::warn!(error = %e, "request failed"));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl FtxClient {
+    pub(super) async fn traced<T>(
+        &self,
+        _request: &PreparedRequest,
+        future: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        future.await
+    }
+}
+
+/// Record the status and attempt count of a sent request on the current span.
+#[cfg(feature = "tracing")]
+pub(super) fn record_attempt(attempt: u32, response: &Result<RawResponse>) {
+    let span = tracing::Span::current();
+    span.record("attempts", attempt);
+    if let Ok(response) = response {
+        span.record("status", response.status.as_u16());
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(super) fn record_attempt(_attempt: u32, _response: &Result<RawResponse>) {}
+
+/// Span of a websocket connection, events about the connection are emitted inside it.
+#[cfg(feature = "websocket")]
+#[derive(Debug, Clone)]
+pub(super) struct WsSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+#[cfg(all(feature = "websocket", feature = "tracing"))]
+impl WsSpan {
+    pub(super) fn new(url: &str) -> Self {
+        Self {
+            span: tracing::info_span!("ftx.websocket", url),
+        }
+    }
+
+    pub(super) async fn instrument<T>(&self, future: impl Future<Output = T>) -> T {
+        tracing::Instrument::instrument(future, self.span.clone()).await
+    }
+
+    pub(super) fn connected<T>(&self, result: &Result<T>) {
+        let _enter = self.span.enter();
+        match result {
+            Ok(_) => tracing::info!("websocket connected"),
+            Err(e) => tracing::warn!(error = %e, "websocket connection failed"),
+        }
+    }
+
+    pub(super) fn login(&self, subaccount: Option<&str>) {
+        let _enter = self.span.enter();
+        tracing::info!(subaccount, "websocket login");
+    }
+
+    pub(super) fn sending(&self, message: &WsOutMessage) {
+        let _enter = self.span.enter();
+        match message {
+            WsOutMessage::Subscribe { channel } => tracing::info!(?channel, "subscribing"),
+            WsOutMessage::Unsubscribe { channel } => tracing::info!(?channel, "unsubscribing"),
+            WsOutMessage::Login { .. } => tracing::debug!("sending login"),
+            WsOutMessage::Ping => tracing::trace!("sending ping"),
+        }
+    }
+
+    pub(super) fn received(&self, message: &Result<WsInMessage>) {
+        let _enter = self.span.enter();
+        match message {
+            Ok(WsInMessage::Partial { data }) | Ok(WsInMessage::Update { data }) => {
+                tracing::debug!(channel = data.channel_name(), "message received")
+            }
+            Ok(WsInMessage::Error { code, msg }) => tracing::warn!(code, %msg, "error received"),
+            Ok(message) => tracing::debug!(?message, "message received"),
+            Err(e) => tracing::warn!(error = %e, "invalid message received"),
+        }
+    }
+}
+
+#[cfg(all(feature = "websocket", not(feature = "tracing")))]
+impl WsSpan {
+    pub(super) fn new(_url: &str) -> Self {
+        Self {}
+    }
+
+    pub(super) async fn instrument<T>(&self, future: impl Future<Output = T>) -> T {
+        future.await
+    }
+
+    pub(super) fn connected<T>(&self, _result: &Result<T>) {}
+
+    pub(super) fn login(&self, _subaccount: Option<&str>) {}
+
+    pub(super) fn sending(&self, _message: &WsOutMessage) {}
+
+    pub(super) fn received(&self, _message: &Result<WsInMessage>) {}
+}
@@ -1,43 +1,85 @@
-use serde::Serialize;
+use percent_encoding::{utf8_percent_encode, AsciiSet, PercentEncode, NON_ALPHANUMERIC};
+use serde::{ser, Serialize};
 use serde_json::{to_string, to_value, Value};
 
 pub trait ToUrlQuery: Serialize {
-    fn to_url_query(&self) -> Vec<(String, String)> {
-        let v = to_value(self).unwrap();
-        let v = match v {
-            Value::Null => return vec![],
-            Value::Object(v) => v,
-            _ => panic!("expected struct as url params"),
+    /// Query parameters of a request struct. `None` fields are skipped, arrays are
+    /// joined with commas and nested objects are sent as json.
+    fn to_url_query(&self) -> Result<Vec<(String, String)>, serde_json::Error> {
+        let fields = match to_value(self)? {
+            Value::Null => return Ok(vec![]),
+            Value::Object(fields) => fields,
+            v => {
+                return Err(ser::Error::custom(format!(
+                    "expected a struct as url query, got {}",
+                    v
+                )))
+            }
         };
 
-        let mut vec: Vec<(String, String)> = vec![];
-
-        for (key, value) in v.into_iter() {
-            match value {
+        let mut query = vec![];
+        for (key, value) in fields {
+            let value = match value {
                 Value::Null => continue,
-                Value::String(s) => vec.push((key, s)),
-                Value::Array(a) => vec.push((
-                    key,
-                    a.iter()
-                        .map(|v| v.as_str().unwrap())
-                        .collect::<Vec<_>>()
-                        .join(","),
-                )),
-                v => vec.push((key, to_string(&v).unwrap())),
-            }
+                Value::Array(values) => values
+                    .iter()
+                    .map(query_value)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(","),
+                v => query_value(&v)?,
+            };
+            query.push((key, value));
         }
-        vec
+        Ok(query)
+    }
+}
+
+fn query_value(value: &Value) -> Result<String, serde_json::Error> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        v => to_string(v),
     }
 }
 
 impl<S: Serialize> ToUrlQuery for S {}
 
+/// Characters escaped by javascript's `encodeURIComponent`, which FTX expects.
+const URI_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+pub fn encode_uri_component(s: &str) -> String {
+    utf8_percent_encode(s, URI_COMPONENT).to_string()
+}
+
+/// Characters left as they are in path segments, the unreserved set of RFC 3986.
+/// Escaping dots wouldn't stop `.` and `..` segments from being resolved, requests
+/// reject those in `validate`, see [`PathParam`](crate::request::PathParam).
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+pub fn encode_path_segment(s: &str) -> PercentEncode<'_> {
+    utf8_percent_encode(s, PATH_SEGMENT)
+}
+
 pub trait HeaderBuilder {
     fn add_header(self, key: &str, value: &str) -> Self;
 }
 
+#[cfg(feature = "websocket")]
 use tokio_tungstenite::tungstenite::http::request::Builder as TungsteniteRequestBuilder;
 
+#[cfg(feature = "websocket")]
 impl HeaderBuilder for TungsteniteRequestBuilder {
     fn add_header(self, key: &str, value: &str) -> Self {
         self.header(key, value)
@@ -51,3 +93,51 @@ impl HeaderBuilder for ReqwestBuilder {
         self.header(key, value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ToUrlQuery;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Query {
+        market: &'static str,
+        ids: Vec<u64>,
+        flags: [bool; 2],
+        post_only: bool,
+        limit: Option<u32>,
+        nested: serde_json::Value,
+    }
+
+    #[test]
+    fn encodes_query_values() {
+        let query = Query {
+            market: "BTC/USD",
+            ids: vec![1, 2, 3],
+            flags: [true, false],
+            post_only: true,
+            limit: None,
+            nested: json!({ "a": [1, "b"] }),
+        };
+        let mut query = query.to_url_query().unwrap();
+        query.sort();
+        let query: Vec<_> = query
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("flags", "true,false"),
+                ("ids", "1,2,3"),
+                ("market", "BTC/USD"),
+                ("nested", r#"{"a":[1,"b"]}"#),
+                ("post_only", "true"),
+            ],
+            query
+        );
+
+        assert!(().to_url_query().unwrap().is_empty());
+        assert!(5.to_url_query().is_err());
+    }
+}
@@ -1,4 +1,3 @@
-use anyhow::{anyhow, Context as AnyhowContext, Result};
 use futures::{
     sink::{Sink, SinkExt},
     stream::Stream,
@@ -6,61 +5,115 @@ use futures::{
 };
 use log::debug;
 use pin_project::pin_project;
-use std::pin::Pin;
+use std::{
+    io::{Error as IoError, ErrorKind},
+    pin::Pin,
+};
 use tokio::net::TcpStream;
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+use tokio_tungstenite::client_async;
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+use tokio_tungstenite::client_async_tls;
 use tokio_tungstenite::{
-    connect_async,
-    tungstenite::{http::Request as HttpRequest, protocol::Message as TungsteniteWSMessage},
+    tungstenite::{self, http::Request as HttpRequest, protocol::Message as TungsteniteWSMessage},
     MaybeTlsStream, WebSocketStream,
 };
 
+use url::Url;
+
+mod connect;
+
+use super::{trace::WsSpan, Metrics};
+
 use crate::{
     client::FtxClient,
+    error::{FtxError, Result},
     model::websocket::{LoginArgs, WsInMessage, WsOutMessage},
 };
 
 type WSStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
-const WS_URL: &str = "wss://ftx.com/ws/";
-
 #[pin_project]
 pub struct FtxWebsocket {
     #[pin]
     stream: WSStream,
+    span: WsSpan,
+    metrics: Option<Metrics>,
 }
 
 impl FtxClient {
+    /// Open a websocket connection, using the proxy, local address and
+    /// timeouts configured on the client builder.
     pub async fn websocket(&self) -> Result<FtxWebsocket> {
-        let request = HttpRequest::builder()
-            .uri(WS_URL)
-            .header("user-agent", "ftx-rs");
-
-        let (stream, _) = connect_async(request.body(())?).await?;
-
-        Ok(FtxWebsocket { stream })
+        let span = WsSpan::new(&self.config.ws_url);
+        let connect = async {
+            let url = Url::parse(&self.config.ws_url)?;
+            let request = HttpRequest::builder()
+                .uri(&self.config.ws_url)
+                .header("user-agent", &self.config.user_agent)
+                .body(())?;
+
+            let tcp = connect::connect(&self.config.network, &url)
+                .await
+                .map_err(tungstenite::Error::Io)?;
+
+            #[cfg(any(feature = "rustls", feature = "native-tls"))]
+            let (stream, _) = client_async_tls(request, tcp).await?;
+            #[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+            let (stream, _) = {
+                if url.scheme() == "wss" {
+                    return Err(FtxError::Config(
+                        "wss:// requires the `rustls` or `native-tls` feature".into(),
+                    ));
+                }
+                client_async(request, MaybeTlsStream::Plain(tcp)).await?
+            };
+            Ok(stream)
+        };
+
+        let stream = span
+            .instrument(async {
+                match self.config.network.timeout {
+                    Some(timeout) => {
+                        tokio::time::timeout(timeout, connect).await.map_err(|_| {
+                            tungstenite::Error::Io(IoError::new(
+                                ErrorKind::TimedOut,
+                                "websocket connection timed out",
+                            ))
+                        })?
+                    }
+                    None => connect.await,
+                }
+            })
+            .await;
+        span.connected(&stream);
+
+        Ok(FtxWebsocket {
+            stream: stream?,
+            span,
+            metrics: self.config.metrics.clone(),
+        })
     }
 
     pub async fn send_ws_auth_msg(&self, ws: &mut FtxWebsocket) -> Result<()> {
-        let auth = self
-            .auth
-            .as_ref()
-            .ok_or_else(|| anyhow!("missing auth keys"))?;
+        let auth = self.auth.as_ref().ok_or(FtxError::MissingAuth)?;
 
-        let timestamp = chrono::Utc::now().timestamp_millis();
+        self.refresh_clock().await;
+        let timestamp = self.clock.now_ms();
 
         let prehash = format!("{}websocket_login", timestamp,);
-        let signature = auth.sign(&prehash)?;
-
-        Ok(ws
-            .send(WsOutMessage::Login {
-                args: LoginArgs {
-                    key: &auth.public_key,
-                    time: timestamp,
-                    sign: &signature,
-                    subaccount: auth.subaccount.as_deref(),
-                },
-            })
-            .await?)
+        let signature = auth.signer.sign(&prehash).await?;
+        ws.span.login(auth.subaccount.as_deref());
+
+        ws.send(WsOutMessage::Login {
+            args: LoginArgs {
+                key: &auth.public_key,
+                time: timestamp,
+                sign: &signature,
+                subaccount: auth.subaccount.as_deref(),
+            },
+        })
+        .await
     }
 }
 
@@ -69,20 +122,32 @@ impl Stream for FtxWebsocket {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         let this = self.project();
+        let (span, metrics) = (&*this.span, &*this.metrics);
         let poll = this.stream.poll_next(cx);
-        poll.map(|msg| msg.map(|msg| msg.map_err(anyhow::Error::from).and_then(parse_message)))
+        poll.map(|msg| {
+            msg.map(|msg| {
+                let msg = msg.map_err(FtxError::from).and_then(parse_message);
+                span.received(&msg);
+                if let Some(metrics) = metrics {
+                    metrics.received(&msg);
+                }
+                msg
+            })
+        })
     }
 }
 
 fn parse_message(msg: TungsteniteWSMessage) -> Result<WsInMessage> {
     let msg = match msg {
         TungsteniteWSMessage::Text(msg) => msg,
-        TungsteniteWSMessage::Binary(_) => return Err(anyhow!("unexpected binary contents")),
+        TungsteniteWSMessage::Binary(_) => {
+            return Err(FtxError::UnexpectedMessage("binary contents".into()))
+        }
         TungsteniteWSMessage::Pong(..) => {
-            return Err(anyhow!("recieved pong in unexpected format"))
+            return Err(FtxError::UnexpectedMessage("pong frame".into()))
         }
         TungsteniteWSMessage::Ping(..) => {
-            return Err(anyhow!("recieved ping in unexpected format"))
+            return Err(FtxError::UnexpectedMessage("ping frame".into()))
         }
         TungsteniteWSMessage::Close(..) => {
             return Ok(WsInMessage::Closed);
@@ -91,11 +156,14 @@ fn parse_message(msg: TungsteniteWSMessage) -> Result<WsInMessage> {
 
     debug!("Incoming websocket message {}", msg);
 
-    serde_json::from_str(&msg).with_context(|| format!("could not deserialize {}", msg))
+    serde_json::from_str(&msg).map_err(|source| FtxError::Deserialize {
+        source,
+        payload: msg,
+    })
 }
 
 impl<'a> Sink<WsOutMessage<'a>> for FtxWebsocket {
-    type Error = anyhow::Error;
+    type Error = FtxError;
 
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
         let this = self.project();
@@ -103,7 +171,8 @@ impl<'a> Sink<WsOutMessage<'a>> for FtxWebsocket {
     }
 
     fn start_send(self: Pin<&mut Self>, msg: WsOutMessage<'a>) -> Result<(), Self::Error> {
-        let msg = serde_json::to_string(&msg)?;
+        self.span.sending(&msg);
+        let msg = serde_json::to_string(&msg).map_err(FtxError::Serialize)?;
         debug!("Sending '{}' through websocket", msg);
         let this = self.project();
         Ok(this.stream.start_send(TungsteniteWSMessage::Text(msg))?)
@@ -0,0 +1,173 @@
+use percent_encoding::percent_decode_str;
+use std::{
+    io::{Error, ErrorKind},
+    net::{IpAddr, SocketAddr},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{lookup_host, TcpSocket, TcpStream},
+};
+use url::Url;
+
+use crate::client::NetworkConfig;
+
+/// Largest proxy response header accepted when opening a tunnel.
+const MAX_PROXY_RESPONSE: usize = 8 * 1024;
+
+/// Open a TCP connection to the host of `url`, through the proxy if there is one.
+pub(super) async fn connect(config: &NetworkConfig, url: &Url) -> Result<TcpStream, Error> {
+    let (host, port) = host_port(url)?;
+
+    let connect = async {
+        match &config.proxy {
+            Some(proxy) => {
+                let (proxy_host, proxy_port) = host_port(proxy)?;
+                let mut stream = open(proxy_host, proxy_port, config.local_address).await?;
+                tunnel(&mut stream, proxy, host, port).await?;
+                Ok(stream)
+            }
+            None => open(host, port, config.local_address).await,
+        }
+    };
+
+    match config.connect_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, connect)
+            .await
+            .map_err(|_| Error::new(ErrorKind::TimedOut, "connect timed out"))?,
+        None => connect.await,
+    }
+}
+
+fn host_port(url: &Url) -> Result<(&str, u16), Error> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("no host or port in {}", url),
+        )
+    };
+    let host = url.host_str().ok_or_else(invalid)?;
+    let port = url.port_or_known_default().ok_or_else(invalid)?;
+    // IPv6 hosts are bracketed in urls
+    Ok((host.trim_start_matches('[').trim_end_matches(']'), port))
+}
+
+async fn open(host: &str, port: u16, local_address: Option<IpAddr>) -> Result<TcpStream, Error> {
+    let mut last_error = None;
+    for addr in lookup_host((host, port)).await? {
+        if local_address.is_some_and(|local| local.is_ipv4() != addr.is_ipv4()) {
+            continue;
+        }
+        match connect_from(addr, local_address).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        Error::new(
+            ErrorKind::AddrNotAvailable,
+            format!("no usable address for {}", host),
+        )
+    }))
+}
+
+async fn connect_from(addr: SocketAddr, local_address: Option<IpAddr>) -> Result<TcpStream, Error> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    if let Some(local) = local_address {
+        socket.bind(SocketAddr::new(local, 0))?;
+    }
+    socket.connect(addr).await
+}
+
+/// Ask an http proxy to open a tunnel to `host:port` on `stream`.
+async fn tunnel(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16) -> Result<(), Error> {
+    let target = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]:{}", host, port),
+        _ => format!("{}:{}", host, port),
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if !proxy.username().is_empty() {
+        let decode = |s| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        let credentials = format!(
+            "{}:{}",
+            decode(proxy.username()),
+            decode(proxy.password().unwrap_or_default())
+        );
+        request += &format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::encode(credentials)
+        );
+    }
+    request += "\r\n";
+    stream.write_all(request.as_bytes()).await?;
+
+    // read byte by byte so that nothing after the proxy response is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_PROXY_RESPONSE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "proxy response too long",
+            ));
+        }
+        let byte = stream.read_u8().await?;
+        response.push(byte);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!("proxy refused to connect to {}: {}", target, status_line),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::connect;
+    use crate::client::NetworkConfig;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn tunnels_through_proxy() {
+        let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_url = format!("http://user:p%40ss@{}", proxy.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = proxy.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let len = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).into_owned();
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\nhello")
+                .await
+                .unwrap();
+            request
+        });
+
+        let config = NetworkConfig {
+            proxy: Some(proxy_url.parse().unwrap()),
+            ..Default::default()
+        };
+        let mut stream = connect(&config, &"wss://ftx.com/ws/".parse().unwrap())
+            .await
+            .unwrap();
+        let mut greeting = String::new();
+        stream.read_to_string(&mut greeting).await.unwrap();
+        assert_eq!("hello", greeting);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("CONNECT ftx.com:443 HTTP/1.1\r\n"));
+        // base64 of `user:p@ss`
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwQHNz\r\n"));
+    }
+}
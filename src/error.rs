@@ -0,0 +1,91 @@
+use reqwest::StatusCode;
+use thiserror::Error;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite;
+
+pub type Result<T, E = FtxError> = std::result::Result<T, E>;
+
+/// Errors returned by [`FtxClient`](crate::FtxClient) and
+/// [`FtxWebsocket`](crate::FtxWebsocket).
+#[derive(Debug, Error)]
+pub enum FtxError {
+    /// The request could not be sent or the response could not be read.
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// Non-success http status without an FTX error message in the body.
+    #[error("http error {status}; body: {body}")]
+    Http { status: StatusCode, body: String },
+    /// The exchange rejected the request, `error` is the message from
+    /// the `{"success": false, "error": ...}` body.
+    #[error("FTX API error ({status}): {error}")]
+    Api { status: StatusCode, error: String },
+    #[error("error {source} while deserializing {payload}")]
+    Deserialize {
+        source: serde_json::Error,
+        payload: String,
+    },
+    #[error("failed to serialize request: {0}")]
+    Serialize(#[source] serde_json::Error),
+    /// The request has contradictory or out of range parameters and was not sent,
+    /// see [`Request::validate`](crate::request::Request::validate).
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    /// More items share one second than fit on a page, so pagination can't move
+    /// past them without skipping some. Retry with a larger `limit` or a narrower window.
+    #[error("pagination stalled: {0}")]
+    Pagination(String),
+    #[error("no auth data present")]
+    MissingAuth,
+    #[error("failed to sign message: {0}")]
+    Signing(String),
+    #[error("invalid url: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("invalid client configuration: {0}")]
+    Config(String),
+    /// No client is registered under this name in an [`FtxAccounts`](crate::FtxAccounts).
+    #[error("unknown account {0}")]
+    UnknownAccount(String),
+    /// Credentials could not be loaded from the environment or a credentials file.
+    #[error("failed to load credentials: {0}")]
+    Credentials(String),
+    /// Recording to or replaying from a cassette file failed,
+    /// including replayed requests that were never recorded.
+    #[error("cassette error: {0}")]
+    Cassette(String),
+    #[cfg(feature = "websocket")]
+    #[error("websocket error: {0}")]
+    Websocket(Box<tungstenite::Error>),
+    /// A websocket frame that is not a json text message.
+    #[error("unexpected websocket message: {0}")]
+    UnexpectedMessage(String),
+}
+
+impl FtxError {
+    /// Http status of the response, if one was received.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            FtxError::Http { status, .. } | FtxError::Api { status, .. } => Some(*status),
+            FtxError::Transport(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Whether the exchange rejected the request because of rate limits.
+    pub fn is_rate_limit(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl From<tungstenite::Error> for FtxError {
+    fn from(e: tungstenite::Error) -> Self {
+        FtxError::Websocket(Box::new(e))
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl From<tungstenite::http::Error> for FtxError {
+    fn from(e: tungstenite::http::Error) -> Self {
+        tungstenite::Error::from(e).into()
+    }
+}
@@ -1,6 +1,22 @@
 #![warn(clippy::all)]
 
+#[cfg(feature = "blocking")]
+mod blocking;
 mod client;
+mod error;
 pub mod model;
+#[cfg(feature = "testkit")]
+pub mod testkit;
 
-pub use client::{request, FtxClient};
+#[cfg(feature = "websocket")]
+pub use client::FtxWebsocket;
+pub use client::{
+    request, ApiResponse, AsyncSigner, Credentials, Deployment, EndpointMetrics, FtxAccounts,
+    FtxClient, FtxClientBuilder, HmacSigner, Interceptor, LatencyHistogram, Metrics,
+    MetricsSnapshot, PerAccount, Quota, RateLimiter, RequestInfo, ResponseInfo, RetryPolicy,
+    Secret, Signer,
+};
+pub use error::{FtxError, Result};
+
+#[cfg(feature = "blocking")]
+pub use blocking::{FtxBlockingClient, Paginate};
@@ -346,7 +346,7 @@ pub enum TriggerInfo {
 #[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Trigger {
-    time: DateTime<Utc>,
+    pub time: DateTime<Utc>,
     #[serde(flatten)]
-    info: TriggerInfo,
+    pub info: TriggerInfo,
 }
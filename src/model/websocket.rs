@@ -100,6 +100,20 @@ pub enum ChannelData {
     Orders { data: model::Order },
 }
 
+impl ChannelData {
+    /// Name of the channel the data was sent on, e.g. `orderbook`.
+    pub fn channel_name(&self) -> &'static str {
+        match self {
+            ChannelData::Orderbook { .. } => "orderbook",
+            ChannelData::Trades { .. } => "trades",
+            ChannelData::Ticker { .. } => "ticker",
+            ChannelData::Markets { .. } => "markets",
+            ChannelData::Fills => "fills",
+            ChannelData::Orders { .. } => "orders",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WsInMessage {
@@ -107,6 +121,7 @@ pub enum WsInMessage {
         #[serde(flatten)]
         channel: Channel,
     },
+    #[serde(rename = "unsubscribed")]
     Unsubsribed {
         #[serde(flatten)]
         channel: Channel,
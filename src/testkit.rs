@@ -0,0 +1,282 @@
+//! In-process stand-in for the FTX exchange, for integration tests that can't reach the real one.
+//!
+//! [`MockServer`] serves the REST endpoints covered by [`request`](crate::request) and the
+//! websocket channels of [`Channel`], checks `FTX-KEY`/`FTX-SIGN`/`FTX-TS` signatures
+//! and websocket logins, and keeps balances and orders in memory.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use ftx_rs::{request, testkit::MockServer};
+//!
+//! let server = MockServer::start().await?;
+//! server.add_account("key", "secret");
+//! server.add_market("BTC/USD", "BTC", "USD", "50000".parse()?);
+//! server.set_balance("key", None, "USD", "100000".parse()?);
+//!
+//! let client = server.client_builder().auth("key", "secret").build()?;
+//! let balances = client.request(request::Balances).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request as HttpRequest, Response as HttpResponse, Server,
+};
+use reqwest::{Method, StatusCode};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    io,
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    sync::{Arc, Mutex},
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+use url::form_urlencoded;
+
+use crate::{
+    model::{websocket::Channel, OrderSide},
+    FtxClientBuilder,
+};
+
+mod state;
+mod ws;
+
+use state::{error, Account, Failure, IncomingRequest, MarketRecord, State, Wallet};
+
+/// Emulated FTX exchange listening on localhost, shut down when dropped.
+pub struct MockServer {
+    state: Arc<Mutex<State>>,
+    rest_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Start REST and websocket servers on free local ports.
+    /// Must be called from within a tokio runtime.
+    pub async fn start() -> io::Result<Self> {
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let rest_listener = StdTcpListener::bind("127.0.0.1:0")?;
+        rest_listener.set_nonblocking(true)?;
+        let rest_addr = rest_listener.local_addr()?;
+        let rest_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = rest_state.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle_http(state.clone(), req))) }
+        });
+        let server = Server::from_tcp(rest_listener)
+            .map_err(io::Error::other)?
+            .serve(make_service);
+
+        let ws_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let ws_addr = ws_listener.local_addr()?;
+
+        let tasks = vec![
+            tokio::spawn(async move {
+                let _ = server.await;
+            }),
+            tokio::spawn(ws::serve(ws_listener, state.clone())),
+        ];
+
+        Ok(Self {
+            state,
+            rest_addr,
+            ws_addr,
+            tasks,
+        })
+    }
+
+    pub fn rest_url(&self) -> String {
+        format!("http://{}/api", self.rest_addr)
+    }
+
+    pub fn websocket_url(&self) -> String {
+        format!("ws://{}/ws/", self.ws_addr)
+    }
+
+    /// Client builder pointed at this server, credentials still have to be added.
+    pub fn client_builder(&self) -> FtxClientBuilder {
+        FtxClientBuilder::new()
+            .rest_url(self.rest_url())
+            .websocket_url(self.websocket_url())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Register API credentials, each key gets its own main account.
+    pub fn add_account(&self, public_key: &str, private_key: &str) {
+        self.state().accounts.insert(
+            public_key.into(),
+            Account {
+                secret: private_key.into(),
+                main: Wallet::default(),
+                subaccounts: BTreeMap::new(),
+            },
+        );
+    }
+
+    /// # Panics
+    /// If `public_key` wasn't registered with [`add_account`](Self::add_account).
+    pub fn add_subaccount(&self, public_key: &str, nickname: &str) {
+        self.state()
+            .accounts
+            .get_mut(public_key)
+            .expect("unknown account")
+            .subaccounts
+            .insert(nickname.into(), Wallet::default());
+    }
+
+    /// # Panics
+    /// If the account or subaccount doesn't exist.
+    pub fn set_balance(
+        &self,
+        public_key: &str,
+        subaccount: Option<&str>,
+        coin: &str,
+        total: Decimal,
+    ) {
+        self.state()
+            .wallet(public_key, subaccount)
+            .expect("unknown account")
+            .balances
+            .insert(coin.into(), total);
+    }
+
+    /// # Panics
+    /// If the account or subaccount doesn't exist.
+    pub fn balance(&self, public_key: &str, subaccount: Option<&str>, coin: &str) -> Decimal {
+        self.state()
+            .wallet(public_key, subaccount)
+            .expect("unknown account")
+            .balances
+            .get(coin)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// List a spot market, market orders are filled at `price`.
+    pub fn add_market(&self, name: &str, base: &str, quote: &str, price: Decimal) {
+        self.state().markets.insert(
+            name.into(),
+            MarketRecord {
+                name: name.into(),
+                base: base.into(),
+                quote: quote.into(),
+                price,
+            },
+        );
+    }
+
+    /// Add a public trade returned by the `Trades` endpoint, returns its id.
+    pub fn add_trade(
+        &self,
+        market: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+        time: DateTime<Utc>,
+    ) -> u64 {
+        self.state().add_trade(market, side, price, size, time)
+    }
+
+    /// Fill an open order at its limit price, updating balances and
+    /// notifying the `orders` websocket channel.
+    pub fn fill_order(
+        &self,
+        public_key: &str,
+        subaccount: Option<&str>,
+        order_id: u64,
+    ) -> Result<(), String> {
+        self.state()
+            .fill_order(public_key, subaccount, order_id)
+            .map_err(|(_, body)| body["error"].as_str().unwrap_or_default().to_owned())
+    }
+
+    /// Answer the next `method` request whose path (without `/api`) starts with `path`
+    /// with `status` and an FTX error body. Queued failures are used in order.
+    pub fn fail_next(&self, method: Method, path: &str, status: StatusCode, error: &str) {
+        self.state().failures.push_back(Failure {
+            method,
+            path: path.into(),
+            status,
+            error: error.into(),
+        });
+    }
+
+    /// Shift the server clock, affecting `/time` and the accepted `FTX-TS` and
+    /// websocket login timestamps.
+    pub fn set_clock_offset(&self, offset: ChronoDuration) {
+        self.state().clock_offset_ms = offset.num_milliseconds();
+    }
+
+    /// Send an `update` message with `data` to websocket connections subscribed to `channel`.
+    pub fn publish(&self, channel: &Channel, data: Value) {
+        self.publish_message(channel, "update", data)
+    }
+
+    /// Send a `partial` message with `data` to websocket connections subscribed to `channel`.
+    pub fn publish_partial(&self, channel: &Channel, data: Value) {
+        self.publish_message(channel, "partial", data)
+    }
+
+    fn publish_message(&self, channel: &Channel, kind: &str, data: Value) {
+        let channel = serde_json::to_value(channel).expect("channels serialize to json");
+        self.state().publish(&channel, kind, data);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn handle_http(
+    state: Arc<Mutex<State>>,
+    req: HttpRequest<Body>,
+) -> Result<HttpResponse<Body>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let body = String::from_utf8_lossy(&body);
+
+    let (status, reply) = match parts.uri.path().strip_prefix("/api") {
+        Some(path) => {
+            let query = parts
+                .uri
+                .query()
+                .map(|q| form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+                .unwrap_or_default();
+            let headers: HashMap<_, _> = parts
+                .headers
+                .iter()
+                .filter_map(|(k, v)| Some((k.as_str().to_owned(), v.to_str().ok()?.to_owned())))
+                .collect();
+            let signed_path = parts.uri.path_and_query().map_or("", |p| p.as_str());
+
+            state.lock().unwrap().handle(&IncomingRequest {
+                method: &parts.method,
+                path,
+                signed_path,
+                query,
+                headers,
+                body: &body,
+            })
+        }
+        None => error(StatusCode::NOT_FOUND, "Not found"),
+    };
+
+    Ok(HttpResponse::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(reply.to_string()))
+        .unwrap())
+}
@@ -0,0 +1,959 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use percent_encoding::percent_decode_str;
+use reqwest::{Method, StatusCode};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::model::OrderSide;
+
+/// How far `FTX-TS` may be from the server clock before a request is rejected.
+pub(super) const MAX_TIMESTAMP_DRIFT_MS: i64 = 30_000;
+
+pub(super) type Reply = (StatusCode, Value);
+
+fn ok(result: Value) -> Reply {
+    (StatusCode::OK, json!({ "success": true, "result": result }))
+}
+
+pub(super) fn error(status: StatusCode, message: &str) -> Reply {
+    (status, json!({ "success": false, "error": message }))
+}
+
+fn side_str(side: &OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct MarketRecord {
+    pub(super) name: String,
+    pub(super) base: String,
+    pub(super) quote: String,
+    pub(super) price: Decimal,
+}
+
+impl MarketRecord {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "type": "spot",
+            "baseCurrency": self.base,
+            "quoteCurrency": self.quote,
+            "enabled": true,
+            "ask": self.price,
+            "bid": self.price,
+            "price": self.price,
+            "last": self.price,
+            "postOnly": false,
+            "priceIncrement": "0.01".parse::<Decimal>().unwrap(),
+            "sizeIncrement": "0.0001".parse::<Decimal>().unwrap(),
+            "restricted": false,
+            "minProvideSize": "0.0001".parse::<Decimal>().unwrap(),
+            "highLeverageFeeExempt": false,
+            "change1h": 0.0,
+            "change24h": 0.0,
+            "changeBod": 0.0,
+            "quoteVolume24h": 0.0,
+            "volumeUsd24h": 0.0,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TradeRecord {
+    pub(super) id: u64,
+    pub(super) market: String,
+    pub(super) side: OrderSide,
+    pub(super) price: Decimal,
+    pub(super) size: Decimal,
+    pub(super) time: DateTime<Utc>,
+}
+
+impl TradeRecord {
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "liquidation": false,
+            "side": side_str(&self.side),
+            "price": self.price,
+            "size": self.size,
+            "time": self.time,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct OrderRecord {
+    pub(super) id: u64,
+    market: String,
+    created_at: DateTime<Utc>,
+    limit: bool,
+    side: OrderSide,
+    price: Decimal,
+    size: Decimal,
+    filled_size: Decimal,
+    avg_fill_price: Option<Decimal>,
+    /// `new`, `open` or `closed`
+    status: &'static str,
+    reduce_only: bool,
+    ioc: bool,
+    post_only: bool,
+    client_id: Option<String>,
+}
+
+impl OrderRecord {
+    pub(super) fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "market": self.market,
+            "createdAt": self.created_at,
+            "type": if self.limit { "limit" } else { "market" },
+            "side": side_str(&self.side),
+            "price": self.price,
+            "size": self.size,
+            "filledSize": self.filled_size,
+            "remainingSize": self.size - self.filled_size,
+            "avgFillPrice": self.avg_fill_price,
+            "status": self.status,
+            "future": null,
+            "reduceOnly": self.reduce_only,
+            "ioc": self.ioc,
+            "postOnly": self.post_only,
+            "clientId": self.client_id,
+        })
+    }
+}
+
+/// Balances and orders of an account or one of its subaccounts.
+#[derive(Debug, Default)]
+pub(super) struct Wallet {
+    pub(super) balances: BTreeMap<String, Decimal>,
+    pub(super) orders: Vec<OrderRecord>,
+}
+
+impl Wallet {
+    fn balances_json(&self) -> Value {
+        self.balances
+            .iter()
+            .map(|(coin, total)| {
+                json!({
+                    "coin": coin,
+                    "free": total,
+                    "total": total,
+                    "usdValue": 0.0,
+                    "spotBorrow": Decimal::ZERO,
+                    "availableWithoutBorrow": total,
+                })
+            })
+            .collect()
+    }
+
+    fn balance(&mut self, coin: &str) -> &mut Decimal {
+        self.balances.entry(coin.to_owned()).or_default()
+    }
+
+    /// Fill `order` completely at `price`, moving funds between base and quote balances.
+    fn fill(&mut self, index: usize, market: &MarketRecord, price: Decimal) -> Result<(), Reply> {
+        let order = &self.orders[index];
+        let (size, side) = (order.size - order.filled_size, order.side.clone());
+        let cost = size * price;
+        match side {
+            OrderSide::Buy if *self.balance(&market.quote) < cost => {
+                return Err(error(StatusCode::BAD_REQUEST, "Not enough balances"))
+            }
+            OrderSide::Sell if *self.balance(&market.base) < size => {
+                return Err(error(StatusCode::BAD_REQUEST, "Not enough balances"))
+            }
+            OrderSide::Buy => {
+                *self.balance(&market.quote) -= cost;
+                *self.balance(&market.base) += size;
+            }
+            OrderSide::Sell => {
+                *self.balance(&market.base) -= size;
+                *self.balance(&market.quote) += cost;
+            }
+        }
+
+        let order = &mut self.orders[index];
+        order.filled_size = order.size;
+        order.avg_fill_price = Some(price);
+        order.status = "closed";
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Account {
+    pub(super) secret: String,
+    pub(super) main: Wallet,
+    pub(super) subaccounts: BTreeMap<String, Wallet>,
+}
+
+impl Account {
+    pub(super) fn wallet(&mut self, subaccount: Option<&str>) -> Option<&mut Wallet> {
+        match subaccount {
+            Some(nickname) => self.subaccounts.get_mut(nickname),
+            None => Some(&mut self.main),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Failure {
+    pub(super) method: Method,
+    pub(super) path: String,
+    pub(super) status: StatusCode,
+    pub(super) error: String,
+}
+
+#[derive(Debug)]
+pub(super) struct WsConnection {
+    pub(super) id: u64,
+    pub(super) sender: UnboundedSender<String>,
+    /// Public key and subaccount after a successful login
+    pub(super) login: Option<(String, Option<String>)>,
+    /// Subscribed channels, serialized to json
+    pub(super) subscriptions: Vec<Value>,
+}
+
+/// Everything the mock exchange knows, guarded by a single lock.
+#[derive(Debug, Default)]
+pub(super) struct State {
+    pub(super) accounts: HashMap<String, Account>,
+    pub(super) markets: BTreeMap<String, MarketRecord>,
+    pub(super) trades: Vec<TradeRecord>,
+    pub(super) failures: VecDeque<Failure>,
+    pub(super) connections: Vec<WsConnection>,
+    pub(super) clock_offset_ms: i64,
+    next_id: u64,
+}
+
+/// Request as received by the REST server, with the `/api` prefix already removed from `path`.
+pub(super) struct IncomingRequest<'a> {
+    pub(super) method: &'a Method,
+    pub(super) path: &'a str,
+    /// Path and query exactly as sent, used to verify the signature
+    pub(super) signed_path: &'a str,
+    pub(super) query: HashMap<String, String>,
+    pub(super) headers: HashMap<String, String>,
+    pub(super) body: &'a str,
+}
+
+pub(super) fn verify_signature(secret: &str, prehash: &str, signature: &str) -> bool {
+    let mut mac = match Hmac::<Sha256>::new_from_slice(secret.as_bytes()) {
+        Ok(mac) => mac,
+        Err(_) => return false,
+    };
+    mac.update(prehash.as_bytes());
+    hex::decode(signature).is_ok_and(|signature| mac.verify(&signature).is_ok())
+}
+
+fn decode(segment: &str) -> String {
+    percent_decode_str(segment).decode_utf8_lossy().into_owned()
+}
+
+fn body_json(body: &str) -> Result<Value, Reply> {
+    if body.is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str(body).map_err(|_| error(StatusCode::BAD_REQUEST, "Invalid JSON"))
+}
+
+fn field<'a>(body: &'a Value, name: &str) -> Result<&'a Value, Reply> {
+    match body.get(name) {
+        Some(Value::Null) | None => Err(error(
+            StatusCode::BAD_REQUEST,
+            &format!("Missing parameter {}", name),
+        )),
+        Some(v) => Ok(v),
+    }
+}
+
+fn str_field<'a>(body: &'a Value, name: &str) -> Result<&'a str, Reply> {
+    field(body, name)?.as_str().ok_or_else(|| {
+        error(
+            StatusCode::BAD_REQUEST,
+            &format!("Invalid parameter {}", name),
+        )
+    })
+}
+
+fn decimal_field(body: &Value, name: &str) -> Result<Option<Decimal>, Reply> {
+    match body.get(name) {
+        Some(Value::Null) | None => Ok(None),
+        Some(v) => serde_json::from_value(v.clone()).map(Some).map_err(|_| {
+            error(
+                StatusCode::BAD_REQUEST,
+                &format!("Invalid parameter {}", name),
+            )
+        }),
+    }
+}
+
+fn bool_field(body: &Value, name: &str) -> bool {
+    body.get(name).and_then(Value::as_bool).unwrap_or(false)
+}
+
+impl State {
+    pub(super) fn now(&self) -> DateTime<Utc> {
+        Utc::now() + ChronoDuration::milliseconds(self.clock_offset_ms)
+    }
+
+    pub(super) fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub(super) fn handle(&mut self, request: &IncomingRequest) -> Reply {
+        if let Some(i) = self
+            .failures
+            .iter()
+            .position(|f| f.method == request.method && request.path.starts_with(f.path.as_str()))
+        {
+            let failure = self.failures.remove(i).unwrap();
+            return error(failure.status, &failure.error);
+        }
+
+        let result = match self.route_public(request) {
+            Some(reply) => reply,
+            None => self.authenticate(request).and_then(|(key, subaccount)| {
+                self.route_private(request, &key, subaccount.as_deref())
+            }),
+        };
+        result.unwrap_or_else(|reply| reply)
+    }
+
+    fn route_public(&self, request: &IncomingRequest) -> Option<Result<Reply, Reply>> {
+        if request.method != Method::GET {
+            return None;
+        }
+        let path = request.path;
+
+        if path == "/time" {
+            return Some(Ok(ok(json!(self.now()))));
+        }
+        if path == "/markets" {
+            return Some(Ok(ok(self
+                .markets
+                .values()
+                .map(MarketRecord::to_json)
+                .collect())));
+        }
+
+        let rest = path.strip_prefix("/markets/")?;
+        let (name, sub) = ["/orderbook", "/trades", "/candles"]
+            .iter()
+            .find_map(|suffix| rest.strip_suffix(suffix).map(|name| (name, *suffix)))
+            .unwrap_or((rest, ""));
+        let name = decode(name);
+
+        let market = match self.markets.get(&name) {
+            Some(market) => market,
+            None => {
+                return Some(Err(error(
+                    StatusCode::NOT_FOUND,
+                    &format!("No such market: {}", name),
+                )))
+            }
+        };
+
+        Some(Ok(match sub {
+            "" => ok(market.to_json()),
+            "/orderbook" => ok(json!({ "asks": [], "bids": [] })),
+            "/candles" => ok(json!([])),
+            _ => ok(self.trades_json(&name, &request.query)),
+        }))
+    }
+
+    fn trades_json(&self, market: &str, query: &HashMap<String, String>) -> Value {
+        let (start, end) = time_window(query);
+        let limit = query
+            .get("limit")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(20);
+
+        let mut trades: Vec<_> = self
+            .trades
+            .iter()
+            .filter(|t| t.market == market)
+            .filter(|t| (start..=end).contains(&unix_time(&t.time)))
+            .collect();
+        trades.sort_by(|a, b| b.time.cmp(&a.time).then(b.id.cmp(&a.id)));
+        trades
+            .into_iter()
+            .take(limit)
+            .map(TradeRecord::to_json)
+            .collect()
+    }
+
+    /// Check `FTX-KEY`, `FTX-TS` and `FTX-SIGN`, returning the key and subaccount to act on.
+    fn authenticate(&self, request: &IncomingRequest) -> Result<(String, Option<String>), Reply> {
+        let header = |name: &str| {
+            request
+                .headers
+                .get(name)
+                .ok_or_else(|| error(StatusCode::UNAUTHORIZED, "Not logged in"))
+        };
+        let key = header("ftx-key")?;
+        let timestamp = header("ftx-ts")?;
+        let signature = header("ftx-sign")?;
+
+        let account = self
+            .accounts
+            .get(key)
+            .ok_or_else(|| error(StatusCode::UNAUTHORIZED, "Not logged in: Invalid API key"))?;
+
+        let drift = timestamp
+            .parse::<i64>()
+            .map(|ts| (ts - self.now().timestamp_millis()).abs())
+            .unwrap_or(i64::MAX);
+        if drift > MAX_TIMESTAMP_DRIFT_MS {
+            return Err(error(
+                StatusCode::UNAUTHORIZED,
+                "Not logged in: Invalid timestamp",
+            ));
+        }
+
+        let path = request.signed_path;
+        let prehash = format!(
+            "{}{}{}{}",
+            timestamp,
+            request.method,
+            path.strip_suffix('?').unwrap_or(path),
+            request.body
+        );
+        if !verify_signature(&account.secret, &prehash, signature) {
+            return Err(error(
+                StatusCode::UNAUTHORIZED,
+                "Not logged in: Invalid signature",
+            ));
+        }
+
+        let subaccount = request.headers.get("ftx-subaccount").map(|s| decode(s));
+        if let Some(nickname) = &subaccount {
+            if !account.subaccounts.contains_key(nickname) {
+                return Err(error(
+                    StatusCode::UNAUTHORIZED,
+                    "Not logged in: Invalid subaccount",
+                ));
+            }
+        }
+        Ok((key.clone(), subaccount))
+    }
+
+    fn route_private(
+        &mut self,
+        request: &IncomingRequest,
+        key: &str,
+        subaccount: Option<&str>,
+    ) -> Result<Reply, Reply> {
+        let body = body_json(request.body)?;
+        let segments: Vec<_> = request.path.trim_start_matches('/').split('/').collect();
+        let not_found = || error(StatusCode::NOT_FOUND, "Not found");
+
+        match (request.method.clone(), segments.as_slice()) {
+            (Method::GET, ["account"]) => Ok(ok(self.account_json(key, subaccount))),
+            (Method::GET, ["wallet", "balances"]) => {
+                Ok(ok(self.wallet(key, subaccount)?.balances_json()))
+            }
+            (Method::GET, ["wallet", "all_balances"]) => {
+                let account = &self.accounts[key];
+                let mut all = serde_json::Map::new();
+                all.insert("main".into(), account.main.balances_json());
+                for (nickname, wallet) in &account.subaccounts {
+                    all.insert(nickname.clone(), wallet.balances_json());
+                }
+                Ok(ok(Value::Object(all)))
+            }
+            (Method::GET, ["wallet", "coins"]) => Ok(ok(self.coins_json())),
+            (Method::GET, ["wallet", "deposit_address", coin]) => Ok(ok(json!({
+                "address": format!("mock-{}-address", decode(coin)),
+                "tag": null,
+                "method": request.query.get("method").cloned().unwrap_or_else(|| decode(coin).to_lowercase()),
+            }))),
+            (Method::GET, ["wallet", "deposits"]) | (Method::GET, ["wallet", "withdrawals"]) => {
+                Ok(ok(json!([])))
+            }
+
+            (Method::GET, ["subaccounts"]) => Ok(ok(self.accounts[key]
+                .subaccounts
+                .keys()
+                .map(|nickname| subaccount_json(nickname))
+                .collect())),
+            (Method::POST, ["subaccounts"]) => {
+                let nickname = str_field(&body, "nickname")?.to_owned();
+                let account = self.accounts.get_mut(key).unwrap();
+                if account.subaccounts.contains_key(&nickname) {
+                    return Err(error(StatusCode::BAD_REQUEST, "Nickname already in use"));
+                }
+                account
+                    .subaccounts
+                    .insert(nickname.clone(), Wallet::default());
+                Ok(ok(subaccount_json(&nickname)))
+            }
+            (Method::POST, ["subaccounts", "update_name"]) => {
+                let nickname = str_field(&body, "nickname")?;
+                let new_nickname = str_field(&body, "newNickname")?;
+                let account = self.accounts.get_mut(key).unwrap();
+                let wallet = account
+                    .subaccounts
+                    .remove(nickname)
+                    .ok_or_else(|| error(StatusCode::BAD_REQUEST, "No such subaccount"))?;
+                account.subaccounts.insert(new_nickname.into(), wallet);
+                Ok(ok(Value::Null))
+            }
+            (Method::DELETE, ["subaccounts"]) => {
+                let nickname = str_field(&body, "nickname")?;
+                self.accounts
+                    .get_mut(key)
+                    .unwrap()
+                    .subaccounts
+                    .remove(nickname)
+                    .ok_or_else(|| error(StatusCode::BAD_REQUEST, "No such subaccount"))?;
+                Ok(ok(Value::Null))
+            }
+            (Method::GET, ["subaccounts", nickname, "balances"]) => {
+                let nickname = decode(nickname);
+                Ok(ok(self.wallet(key, Some(&nickname))?.balances_json()))
+            }
+            (Method::POST, ["subaccounts", "transfer"]) => self.transfer(key, &body),
+
+            (Method::GET, ["orders"]) => Ok(ok(self.orders_json(
+                key,
+                subaccount,
+                &request.query,
+                true,
+            )?)),
+            (Method::GET, ["orders", "history"]) => Ok(ok(self.orders_json(
+                key,
+                subaccount,
+                &request.query,
+                false,
+            )?)),
+            (Method::POST, ["orders"]) => self.place_order(key, subaccount, &body),
+            (Method::DELETE, ["orders"]) => {
+                let market = body.get("market").and_then(Value::as_str);
+                let wallet = self.wallet(key, subaccount)?;
+                for order in wallet.orders.iter_mut() {
+                    if order.status != "closed" && market.is_none_or(|m| m == order.market) {
+                        order.status = "closed";
+                    }
+                }
+                Ok(ok(json!("Orders queued for cancelation")))
+            }
+            (method, ["orders", rest @ ..]) => {
+                let (by_client_id, id, modify) = match rest {
+                    ["by_client_id", id] => (true, decode(id), false),
+                    ["by_client_id", id, "modify"] => (true, decode(id), true),
+                    [id] => (false, decode(id), false),
+                    [id, "modify"] => (false, decode(id), true),
+                    _ => return Err(not_found()),
+                };
+                let index = self
+                    .wallet(key, subaccount)?
+                    .orders
+                    .iter()
+                    .position(|o| {
+                        if by_client_id {
+                            o.client_id.as_deref() == Some(id.as_str()) && o.status != "closed"
+                        } else {
+                            o.id.to_string() == id
+                        }
+                    })
+                    .ok_or_else(|| error(StatusCode::NOT_FOUND, "Order not found"))?;
+
+                match (method, modify) {
+                    (Method::GET, false) => {
+                        Ok(ok(self.wallet(key, subaccount)?.orders[index].to_json()))
+                    }
+                    (Method::DELETE, false) => {
+                        let wallet = self.wallet(key, subaccount)?;
+                        if wallet.orders[index].status == "closed" {
+                            return Err(error(StatusCode::BAD_REQUEST, "Order already closed"));
+                        }
+                        wallet.orders[index].status = "closed";
+                        Ok(ok(json!("Order queued for cancellation")))
+                    }
+                    (Method::POST, true) => self.modify_order(key, subaccount, index, &body),
+                    _ => Err(not_found()),
+                }
+            }
+
+            (Method::GET, ["conditional_orders"])
+            | (Method::GET, ["conditional_orders", "history"])
+            | (Method::GET, ["conditional_orders", _, "triggers"]) => Ok(ok(json!([]))),
+            (Method::DELETE, ["conditional_orders", _]) => {
+                Err(error(StatusCode::NOT_FOUND, "Order not found"))
+            }
+
+            _ => Err(not_found()),
+        }
+    }
+
+    pub(super) fn wallet(
+        &mut self,
+        key: &str,
+        subaccount: Option<&str>,
+    ) -> Result<&mut Wallet, Reply> {
+        self.accounts
+            .get_mut(key)
+            .and_then(|account| account.wallet(subaccount))
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "No such subaccount"))
+    }
+
+    fn account_json(&self, key: &str, subaccount: Option<&str>) -> Value {
+        json!({
+            "username": format!("{}@mock", subaccount.unwrap_or(key)),
+            "backstopProvider": false,
+            "collateral": Decimal::ZERO,
+            "freeCollateral": Decimal::ZERO,
+            "leverage": 1.0,
+            "initialMarginRequirement": 0.1,
+            "liquidating": false,
+            "maintenanceMarginRequirement": 0.03,
+            "makerFee": 0.0002,
+            "takerFee": 0.0007,
+            "marginFraction": null,
+            "openMarginFraction": null,
+            "totalAccountValue": 0.0,
+            "totalPositionSize": 0.0,
+            "positionLimit": null,
+            "positionLimitUsed": null,
+            "useFttCollateral": true,
+            "chargeInterestOnNegativeUsd": false,
+            "spotMarginEnabled": false,
+            "spotLendingEnabled": false,
+            "positions": [],
+        })
+    }
+
+    fn coins_json(&self) -> Value {
+        let mut coins: Vec<_> = self
+            .markets
+            .values()
+            .flat_map(|m| vec![m.base.clone(), m.quote.clone()])
+            .collect();
+        coins.sort();
+        coins.dedup();
+        coins
+            .into_iter()
+            .map(|coin| {
+                json!({
+                    "id": coin,
+                    "name": coin,
+                    "fiat": false,
+                    "isToken": false,
+                    "isEtf": false,
+                    "hidden": false,
+                    "canDeposit": true,
+                    "canWithdraw": true,
+                    "canConvert": true,
+                    "collateral": false,
+                    "collateralWeight": 0.0,
+                    "methods": [],
+                    "creditTo": null,
+                    "bep2Asset": null,
+                    "erc20Contract": null,
+                    "splMint": null,
+                    "usdFungible": false,
+                    "hasTag": false,
+                    "spotMargin": false,
+                    "indexPrice": 0.0,
+                })
+            })
+            .collect()
+    }
+
+    fn orders_json(
+        &mut self,
+        key: &str,
+        subaccount: Option<&str>,
+        query: &HashMap<String, String>,
+        open: bool,
+    ) -> Result<Value, Reply> {
+        let market = query.get("market");
+        let (start, end) = time_window(query);
+        let limit = query
+            .get("limit")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100);
+
+        let mut orders: Vec<_> = self
+            .wallet(key, subaccount)?
+            .orders
+            .iter()
+            .filter(|o| !open || o.status != "closed")
+            .filter(|o| market.is_none_or(|m| m == &o.market))
+            .filter(|o| (start..=end).contains(&unix_time(&o.created_at)))
+            .cloned()
+            .collect();
+        orders.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(orders
+            .iter()
+            .take(limit)
+            .map(OrderRecord::to_json)
+            .collect())
+    }
+
+    fn transfer(&mut self, key: &str, body: &Value) -> Result<Reply, Reply> {
+        let coin = str_field(body, "coin")?.to_owned();
+        let size = decimal_field(body, "size")?
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "Missing parameter size"))?;
+        let source = str_field(body, "source")?;
+        let destination = str_field(body, "destination")?;
+        let as_subaccount = |name: &str| (name != "main").then(|| name.to_owned());
+        let (source, destination) = (as_subaccount(source), as_subaccount(destination));
+
+        let from = self.wallet(key, source.as_deref())?.balance(&coin);
+        if *from < size {
+            return Err(error(StatusCode::BAD_REQUEST, "Not enough balances"));
+        }
+        *from -= size;
+        *self.wallet(key, destination.as_deref())?.balance(&coin) += size;
+
+        Ok(ok(json!({
+            "id": self.next_id(),
+            "coin": coin,
+            "size": size,
+            "time": self.now(),
+            "notes": "",
+        })))
+    }
+
+    fn place_order(
+        &mut self,
+        key: &str,
+        subaccount: Option<&str>,
+        body: &Value,
+    ) -> Result<Reply, Reply> {
+        let market_name = str_field(body, "market")?;
+        let market = self
+            .markets
+            .get(market_name)
+            .cloned()
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "No such market"))?;
+        let side: OrderSide = serde_json::from_value(field(body, "side")?.clone())
+            .map_err(|_| error(StatusCode::BAD_REQUEST, "Invalid parameter side"))?;
+        let limit = match str_field(body, "type")? {
+            "limit" => true,
+            "market" => false,
+            _ => return Err(error(StatusCode::BAD_REQUEST, "Invalid parameter type")),
+        };
+        let size = decimal_field(body, "size")?
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "Missing parameter size"))?;
+        let price = match decimal_field(body, "price")? {
+            Some(price) if limit => price,
+            None if limit => return Err(error(StatusCode::BAD_REQUEST, "Missing parameter price")),
+            _ => market.price,
+        };
+        if size <= Decimal::ZERO || price <= Decimal::ZERO {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "Size and price must be positive",
+            ));
+        }
+        let client_id = body
+            .get("clientId")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let id = self.next_id();
+        let created_at = self.now();
+        let wallet = self.wallet(key, subaccount)?;
+        if client_id.is_some()
+            && wallet
+                .orders
+                .iter()
+                .any(|o| o.client_id == client_id && o.status != "closed")
+        {
+            return Err(error(StatusCode::BAD_REQUEST, "Duplicate client order ID"));
+        }
+
+        wallet.orders.push(OrderRecord {
+            id,
+            market: market.name.clone(),
+            created_at,
+            limit,
+            side,
+            price,
+            size,
+            filled_size: Decimal::ZERO,
+            avg_fill_price: None,
+            status: "new",
+            reduce_only: bool_field(body, "reduceOnly"),
+            ioc: bool_field(body, "ioc"),
+            post_only: bool_field(body, "postOnly"),
+            client_id,
+        });
+
+        let index = wallet.orders.len() - 1;
+        if !limit {
+            if let Err(e) = wallet.fill(index, &market, price) {
+                wallet.orders.pop();
+                return Err(e);
+            }
+        }
+
+        let reply = wallet.orders[index].to_json();
+        if wallet.orders[index].status == "new" {
+            // FTX reports freshly placed orders as new and as open afterwards
+            wallet.orders[index].status = "open";
+        }
+        let order = wallet.orders[index].clone();
+        self.publish_order(key, subaccount, &order);
+        Ok(ok(reply))
+    }
+
+    fn modify_order(
+        &mut self,
+        key: &str,
+        subaccount: Option<&str>,
+        index: usize,
+        body: &Value,
+    ) -> Result<Reply, Reply> {
+        let price = decimal_field(body, "price")?;
+        let size = decimal_field(body, "size")?;
+        if price.is_none() && size.is_none() {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "Must modify either price or size",
+            ));
+        }
+        let client_id = body
+            .get("clientId")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let id = self.next_id();
+        let created_at = self.now();
+        let wallet = self.wallet(key, subaccount)?;
+        if wallet.orders[index].status == "closed" {
+            return Err(error(StatusCode::BAD_REQUEST, "Order already closed"));
+        }
+
+        // FTX cancels the order and places a new one with the modified parameters
+        let old = &mut wallet.orders[index];
+        old.status = "closed";
+        let mut new = old.clone();
+        new.id = id;
+        new.created_at = created_at;
+        new.status = "open";
+        new.price = price.unwrap_or(new.price);
+        new.size = size.unwrap_or(new.size);
+        new.client_id = client_id.or(new.client_id);
+        wallet.orders.push(new.clone());
+
+        self.publish_order(key, subaccount, &new);
+        Ok(ok(new.to_json()))
+    }
+
+    /// Fill an open order at its limit price.
+    pub(super) fn fill_order(
+        &mut self,
+        key: &str,
+        subaccount: Option<&str>,
+        id: u64,
+    ) -> Result<(), Reply> {
+        let wallet = self.wallet(key, subaccount)?;
+        let index = wallet
+            .orders
+            .iter()
+            .position(|o| o.id == id && o.status != "closed")
+            .ok_or_else(|| error(StatusCode::NOT_FOUND, "Order not found"))?;
+        let (market, price) = (
+            wallet.orders[index].market.clone(),
+            wallet.orders[index].price,
+        );
+        let market = self.markets[&market].clone();
+
+        let wallet = self.wallet(key, subaccount)?;
+        wallet.fill(index, &market, price)?;
+        let order = wallet.orders[index].clone();
+        self.publish_order(key, subaccount, &order);
+        Ok(())
+    }
+
+    /// Send an update on the `orders` channel to connections logged in to the order's account.
+    fn publish_order(&self, key: &str, subaccount: Option<&str>, order: &OrderRecord) {
+        let msg = json!({
+            "channel": "orders",
+            "type": "update",
+            "data": order.to_json(),
+        })
+        .to_string();
+        let channel = json!({ "channel": "orders" });
+
+        for connection in &self.connections {
+            let logged_in = connection
+                .login
+                .as_ref()
+                .is_some_and(|(k, s)| k == key && s.as_deref() == subaccount);
+            if logged_in && connection.subscriptions.contains(&channel) {
+                let _ = connection.sender.send(msg.clone());
+            }
+        }
+    }
+
+    /// Send `data` to every connection subscribed to `channel`.
+    pub(super) fn publish(&self, channel: &Value, kind: &str, data: Value) {
+        let mut msg = channel.clone();
+        msg["type"] = json!(kind);
+        msg["data"] = data;
+        let msg = msg.to_string();
+
+        for connection in &self.connections {
+            if connection.subscriptions.contains(channel) {
+                let _ = connection.sender.send(msg.clone());
+            }
+        }
+    }
+
+    pub(super) fn add_trade(
+        &mut self,
+        market: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+        time: DateTime<Utc>,
+    ) -> u64 {
+        let id = self.next_id();
+        self.trades.push(TradeRecord {
+            id,
+            market: market.into(),
+            side,
+            price,
+            size,
+            time,
+        });
+        id
+    }
+}
+
+fn subaccount_json(nickname: &str) -> Value {
+    json!({
+        "nickname": nickname,
+        "special": false,
+        "deletable": true,
+        "editable": true,
+        "competition": false,
+    })
+}
+
+/// `start_time` and `end_time` of a query, compared with fractional item times like FTX does.
+fn time_window(query: &HashMap<String, String>) -> (f64, f64) {
+    let param = |name: &str| query.get(name).and_then(|v| v.parse::<f64>().ok());
+    (
+        param("start_time").unwrap_or(f64::NEG_INFINITY),
+        param("end_time").unwrap_or(f64::INFINITY),
+    )
+}
+
+fn unix_time(time: &DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 + f64::from(time.timestamp_subsec_nanos()) / 1e9
+}
@@ -0,0 +1,136 @@
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc::unbounded_channel,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+use super::state::{verify_signature, State, WsConnection, MAX_TIMESTAMP_DRIFT_MS};
+
+pub(super) async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(connection(stream, state.clone()));
+    }
+}
+
+async fn connection(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let ws = match accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(_) => return,
+    };
+    let (mut sink, mut source) = ws.split();
+    let (sender, mut receiver) = unbounded_channel::<String>();
+
+    let id = {
+        let mut state = state.lock().unwrap();
+        let id = state.next_id();
+        state.connections.push(WsConnection {
+            id,
+            sender,
+            login: None,
+            subscriptions: vec![],
+        });
+        id
+    };
+
+    let writer = tokio::spawn(async move {
+        while let Some(msg) = receiver.recv().await {
+            if sink.send(Message::Text(msg)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(msg)) = source.next().await {
+        match msg {
+            Message::Text(msg) => handle_message(&mut state.lock().unwrap(), id, &msg),
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+
+    state.lock().unwrap().connections.retain(|c| c.id != id);
+    writer.abort();
+}
+
+fn ws_error(msg: &str) -> Value {
+    json!({ "type": "error", "code": 400, "msg": msg })
+}
+
+fn handle_message(state: &mut State, id: u64, msg: &str) {
+    let msg: Value = serde_json::from_str(msg).unwrap_or(Value::Null);
+    let reply = match msg["op"].as_str() {
+        Some("ping") => Some(json!({ "type": "pong" })),
+        Some("login") => login(state, id, &msg["args"]),
+        Some(op @ "subscribe") | Some(op @ "unsubscribe") => Some(subscribe(state, id, op, &msg)),
+        _ => Some(ws_error("Invalid op")),
+    };
+
+    if let Some(reply) = reply {
+        if let Some(connection) = state.connections.iter().find(|c| c.id == id) {
+            let _ = connection.sender.send(reply.to_string());
+        }
+    }
+}
+
+/// Successful logins are not acknowledged, like on the real exchange.
+fn login(state: &mut State, id: u64, args: &Value) -> Option<Value> {
+    let key = args["key"].as_str().unwrap_or_default();
+    let time = args["time"].as_i64().unwrap_or_default();
+    let sign = args["sign"].as_str().unwrap_or_default();
+    let subaccount = args["subaccount"].as_str().map(str::to_owned);
+
+    // same window as the `FTX-TS` header of REST requests
+    if (time - state.now().timestamp_millis()).abs() > MAX_TIMESTAMP_DRIFT_MS {
+        return Some(ws_error("Invalid login timestamp"));
+    }
+
+    let valid = state.accounts.get(key).is_some_and(|account| {
+        verify_signature(&account.secret, &format!("{}websocket_login", time), sign)
+            && subaccount
+                .as_ref()
+                .is_none_or(|s| account.subaccounts.contains_key(s))
+    });
+    if !valid {
+        return Some(ws_error("Invalid login credentials"));
+    }
+
+    let connection = state.connections.iter_mut().find(|c| c.id == id)?;
+    connection.login = Some((key.into(), subaccount));
+    None
+}
+
+fn subscribe(state: &mut State, id: u64, op: &str, msg: &Value) -> Value {
+    let name = msg["channel"].as_str().unwrap_or_default();
+    let channel = match (name, msg["market"].as_str()) {
+        ("orderbook", Some(market)) | ("trades", Some(market)) | ("ticker", Some(market)) => {
+            json!({ "channel": name, "market": market })
+        }
+        ("markets", _) | ("fills", _) | ("orders", _) => json!({ "channel": name }),
+        _ => return ws_error("Invalid channel"),
+    };
+
+    let connection = match state.connections.iter_mut().find(|c| c.id == id) {
+        Some(connection) => connection,
+        None => return ws_error("Unknown connection"),
+    };
+    if (name == "fills" || name == "orders") && connection.login.is_none() {
+        return ws_error("Not logged in");
+    }
+
+    let kind = if op == "subscribe" {
+        if !connection.subscriptions.contains(&channel) {
+            connection.subscriptions.push(channel.clone());
+        }
+        "subscribed"
+    } else {
+        connection.subscriptions.retain(|c| c != &channel);
+        "unsubscribed"
+    };
+
+    let mut reply = channel;
+    reply["type"] = json!(kind);
+    reply
+}
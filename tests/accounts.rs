@@ -0,0 +1,46 @@
+mod common;
+
+use common::{client, dec, market_order, server};
+use ftx_rs::{request, FtxAccounts, FtxError};
+
+#[tokio::test]
+async fn multiple_accounts() {
+    let server = server().await;
+    server.add_account("other", "other secret");
+    server.set_balance("other", None, "USD", dec("5000"));
+    server.set_balance("other", None, "BTC", dec("2"));
+    server.add_subaccount("key", "bot");
+    server.set_balance("key", Some("bot"), "USD", dec("60000"));
+
+    let main = client(&server);
+    let mut accounts = FtxAccounts::new();
+    accounts.insert("bot", main.with_subaccount(Some("bot")).unwrap());
+    accounts.insert("main", main);
+    accounts.insert(
+        "other",
+        server
+            .client_builder()
+            .auth("other", "other secret")
+            .build()
+            .unwrap(),
+    );
+    accounts.insert("unknown", server.client_builder().build().unwrap());
+
+    accounts.request("bot", market_order("1")).await.unwrap();
+    assert_eq!(dec("10000"), server.balance("key", Some("bot"), "USD"));
+    match accounts.request("nobody", request::Markets).await {
+        Err(FtxError::UnknownAccount(name)) => assert_eq!("nobody", name),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let balances = accounts.balances().await;
+    assert_eq!(3, balances.results.len());
+    assert!(balances.errors.contains_key("unknown"));
+    let combined = balances.combined();
+    let total = |coin: &str| combined.iter().find(|b| b.coin == coin).unwrap().total;
+    assert_eq!(dec("115000"), total("USD"));
+    assert_eq!(dec("3"), total("BTC"));
+
+    let orders = accounts.open_orders(None).await;
+    assert_eq!(0, orders.combined().count());
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{client, dec, server};
+use ftx_rs::{model::OrderSide, request, FtxBlockingClient};
+
+#[test]
+fn blocking_requests() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(server());
+    for i in 0..5 {
+        let time = Utc.timestamp_opt(1_600_000_000 + i, 0).unwrap();
+        server.add_trade("BTC/USD", OrderSide::Sell, dec("50000"), dec("1"), time);
+    }
+
+    let client = FtxBlockingClient::new(client(&server)).unwrap();
+
+    assert_eq!(1, client.request(request::Markets).unwrap().len());
+    assert_eq!(1, client.request(request::Balances).unwrap().len());
+
+    let trades = client
+        .paginate(request::Trades {
+            market_name: "BTC/USD",
+            limit: Some(2),
+            start_time: None,
+            end_time: None,
+        })
+        .collect::<ftx_rs::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(5, trades.len());
+}
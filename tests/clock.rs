@@ -0,0 +1,86 @@
+mod common;
+
+use chrono::Duration as ChronoDuration;
+use common::{client, server};
+use ftx_rs::{
+    model::websocket::{Channel, WsInMessage, WsOutMessage},
+    request, FtxError,
+};
+use futures::{SinkExt, StreamExt};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+fn assert_skew(expected: ChronoDuration, skew: Option<ChronoDuration>) {
+    let error = (skew.unwrap() - expected).num_milliseconds().abs();
+    assert!(error < 1000, "skew {:?}, expected {:?}", skew, expected);
+}
+
+#[tokio::test]
+async fn sync_clock_corrects_timestamps() {
+    let server = server().await;
+    server.set_clock_offset(ChronoDuration::seconds(45));
+    let client = client(&server);
+
+    match client.request(request::Balances).await {
+        Err(FtxError::Api { status, error }) => {
+            assert_eq!(StatusCode::UNAUTHORIZED, status);
+            assert_eq!("Not logged in: Invalid timestamp", error);
+        }
+        r => panic!("unexpected result {:?}", r),
+    }
+    assert_eq!(None, client.clock_skew());
+
+    let offset = client.sync_clock().await.unwrap();
+    assert_skew(ChronoDuration::seconds(45), Some(offset));
+    assert_skew(ChronoDuration::seconds(45), client.clock_skew());
+    client.request(request::Balances).await.unwrap();
+}
+
+#[tokio::test]
+async fn sync_clock_every_refreshes_offset() {
+    let server = server().await;
+    server.set_clock_offset(ChronoDuration::seconds(45));
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .sync_clock_every(Duration::from_millis(200))
+        .build()
+        .unwrap();
+
+    client.request(request::Balances).await.unwrap();
+    assert_skew(ChronoDuration::seconds(45), client.clock_skew());
+
+    server.set_clock_offset(ChronoDuration::seconds(-45));
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client.request(request::Balances).await.unwrap();
+    assert_skew(ChronoDuration::seconds(-45), client.clock_skew());
+}
+
+#[tokio::test]
+async fn sync_clock_corrects_websocket_logins() {
+    let server = server().await;
+    server.set_clock_offset(ChronoDuration::seconds(-45));
+    let client = client(&server);
+
+    let mut ws = client.websocket().await.unwrap();
+    client.send_ws_auth_msg(&mut ws).await.unwrap();
+    match ws.next().await.unwrap() {
+        Ok(WsInMessage::Error { msg, .. }) => assert_eq!("Invalid login timestamp", msg),
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    client.sync_clock().await.unwrap();
+    let mut ws = client.websocket().await.unwrap();
+    client.send_ws_auth_msg(&mut ws).await.unwrap();
+    ws.send(WsOutMessage::Subscribe {
+        channel: Channel::Orders,
+    })
+    .await
+    .unwrap();
+    match ws.next().await.unwrap() {
+        Ok(WsInMessage::Subscribed {
+            channel: Channel::Orders,
+        }) => {}
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}
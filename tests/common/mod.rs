@@ -0,0 +1,45 @@
+//! Mock exchange setup shared by the integration tests.
+#![allow(dead_code)]
+
+use ftx_rs::{
+    model::OrderSide,
+    request::{self, PlaceOrderTypeInfo},
+    testkit::MockServer,
+    FtxClient,
+};
+use rust_decimal::Decimal;
+
+pub fn dec(s: &str) -> Decimal {
+    s.parse().unwrap()
+}
+
+/// Exchange with a `BTC/USD` market and an account `key` holding 100000 USD.
+pub async fn server() -> MockServer {
+    let server = MockServer::start().await.unwrap();
+    server.add_account("key", "secret");
+    server.add_market("BTC/USD", "BTC", "USD", dec("50000"));
+    server.set_balance("key", None, "USD", dec("100000"));
+    server
+}
+
+/// Client signing requests for the account of [`server`].
+pub fn client(server: &MockServer) -> FtxClient {
+    server
+        .client_builder()
+        .auth("key", "secret")
+        .build()
+        .unwrap()
+}
+
+pub fn market_order(size: &str) -> request::PlaceOrder<'static> {
+    request::PlaceOrder {
+        market: "BTC/USD",
+        side: OrderSide::Buy,
+        type_: PlaceOrderTypeInfo::Market,
+        size: size.parse().unwrap(),
+        reduce_only: false,
+        ioc: false,
+        post_only: false,
+        client_id: None,
+    }
+}
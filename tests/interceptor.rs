@@ -0,0 +1,122 @@
+mod common;
+
+use common::{market_order, server};
+use ftx_rs::{request, FtxClient, FtxError, Interceptor, RequestInfo, ResponseInfo, RetryPolicy};
+use reqwest::{Method, StatusCode};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Interceptor keeping a line per call.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Recorder {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+impl Interceptor for Recorder {
+    fn on_request(&self, request: &RequestInfo) {
+        self.0.lock().unwrap().push(format!(
+            "request {} {} attempt={} signed={} body={}",
+            request.method,
+            request.path,
+            request.attempt,
+            request.signed,
+            request.body.unwrap_or("-"),
+        ));
+    }
+
+    fn on_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        self.0.lock().unwrap().push(format!(
+            "response {} attempt={} status={}",
+            request.path,
+            request.attempt,
+            response.status.as_u16(),
+        ));
+    }
+
+    fn on_error(&self, request: &RequestInfo, error: &FtxError, _latency: Duration) {
+        let kind = match error {
+            FtxError::Transport(_) => "transport",
+            _ => "other",
+        };
+        self.0.lock().unwrap().push(format!(
+            "error {} attempt={} {}",
+            request.path, request.attempt, kind,
+        ));
+    }
+}
+
+#[tokio::test]
+async fn interceptors_see_every_attempt() {
+    let server = server().await;
+    let recorder = Recorder::default();
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1)))
+        .interceptor(recorder.clone())
+        .build()
+        .unwrap();
+
+    server.fail_next(
+        Method::GET,
+        "/markets",
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Service unavailable",
+    );
+    client.request(request::Markets).await.unwrap();
+    assert_eq!(
+        vec![
+            "request GET /api/markets attempt=1 signed=false body=-",
+            "response /api/markets attempt=1 status=503",
+            "request GET /api/markets attempt=2 signed=false body=-",
+            "response /api/markets attempt=2 status=200",
+        ],
+        recorder.take()
+    );
+
+    client.request(market_order("0.1")).await.unwrap();
+    let calls = recorder.take();
+    assert_eq!(2, calls.len());
+    assert!(calls[0].starts_with("request POST /api/orders attempt=1 signed=true body={"));
+    assert!(calls[0].contains(r#""market":"BTC/USD""#));
+    assert_eq!("response /api/orders attempt=1 status=200", calls[1]);
+
+    client
+        .request(request::Orderbook {
+            market_name: "BTC/USD",
+            depth: Some(5),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        "request GET /api/markets/BTC/USD/orderbook?depth=5 attempt=1 signed=false body=-",
+        recorder.take()[0]
+    );
+}
+
+#[tokio::test]
+async fn interceptors_see_transport_errors() {
+    let recorder = Recorder::default();
+    let client = FtxClient::builder()
+        .rest_url("http://127.0.0.1:1")
+        .retry_policy(RetryPolicy::none())
+        .interceptor(recorder.clone())
+        .build()
+        .unwrap();
+
+    let e = client.request(request::Markets).await.unwrap_err();
+    assert!(matches!(e, FtxError::Transport(_)));
+    assert_eq!(
+        vec![
+            "request GET /markets attempt=1 signed=false body=-",
+            "error /markets attempt=1 transport",
+        ],
+        recorder.take()
+    );
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{market_order, server};
+use ftx_rs::{
+    model::websocket::{Channel, WsOutMessage},
+    request, Metrics, RetryPolicy,
+};
+use futures::{SinkExt, StreamExt};
+use reqwest::{Method, StatusCode};
+
+#[tokio::test]
+async fn collects_metrics() {
+    let server = server().await;
+    let metrics = Metrics::new();
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .retry_policy(RetryPolicy::none())
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    client.request(request::Markets).await.unwrap();
+    server.fail_next(
+        Method::GET,
+        "/markets",
+        StatusCode::TOO_MANY_REQUESTS,
+        "Please retry request",
+    );
+    client.request(request::Markets).await.unwrap_err();
+    client
+        .request(request::Orderbook {
+            market_name: "BTC/USD",
+            depth: None,
+        })
+        .await
+        .unwrap();
+
+    let mut ws = client.websocket().await.unwrap();
+    client.send_ws_auth_msg(&mut ws).await.unwrap();
+    ws.send(WsOutMessage::Subscribe {
+        channel: Channel::Orders,
+    })
+    .await
+    .unwrap();
+    ws.next().await.unwrap().unwrap();
+    client.request(market_order("0.1")).await.unwrap();
+    ws.next().await.unwrap().unwrap();
+
+    let snapshot = metrics.snapshot();
+    let markets = &snapshot.endpoints[&("GET".to_owned(), "/markets".to_owned())];
+    assert_eq!(2, markets.requests);
+    assert_eq!(1, markets.rate_limited);
+    assert_eq!(Some(&1), markets.errors.get("rate_limit"));
+    assert_eq!(2, markets.latency.count);
+    let orderbook = ("GET".to_owned(), "/markets/{market}/orderbook".to_owned());
+    assert_eq!(1, snapshot.endpoints[&orderbook].requests);
+    assert!(snapshot.endpoints[&orderbook].errors.is_empty());
+    assert_eq!(Some(&1), snapshot.websocket_messages.get("control"));
+    assert_eq!(Some(&1), snapshot.websocket_messages.get("orders"));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{dec, server};
+use ftx_rs::{model::OrderSide, request, FtxError};
+use futures::TryStreamExt;
+use std::collections::HashSet;
+
+#[tokio::test]
+async fn paginates_trades() {
+    let server = server().await;
+    let client = server.client_builder().build().unwrap();
+    for i in 0..25 {
+        // several trades share each second to exercise page boundaries
+        let time = Utc.timestamp_opt(1_600_000_000 + i / 3, 0).unwrap();
+        server.add_trade("BTC/USD", OrderSide::Buy, dec("50000"), dec("1"), time);
+    }
+
+    let trades: Vec<_> = client
+        .paginate(request::Trades {
+            market_name: "BTC/USD",
+            limit: Some(10),
+            start_time: None,
+            end_time: None,
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: HashSet<_> = trades.iter().map(|t| t.id).collect();
+    assert_eq!(25, trades.len());
+    assert_eq!(25, ids.len());
+
+    let owned = request::owned::Trades::from(request::Trades::new("BTC/USD").with_limit(10));
+    let owned_trades: Vec<_> = client.paginate(owned).try_collect().await.unwrap();
+    assert_eq!(25, owned_trades.len());
+}
+
+#[tokio::test]
+async fn paginates_fractional_times() {
+    let server = server().await;
+    let client = server.client_builder().build().unwrap();
+    for i in 0..25 {
+        // more trades than fit on a page within one second, pages end between them
+        let time = Utc
+            .timestamp_millis_opt(1_600_000_000_000 + i * 40)
+            .unwrap();
+        server.add_trade("BTC/USD", OrderSide::Buy, dec("50000"), dec("1"), time);
+    }
+
+    let trades: Vec<_> = client
+        .paginate(request::Trades::new("BTC/USD").with_limit(4))
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: HashSet<_> = trades.iter().map(|t| t.id).collect();
+    assert_eq!(25, trades.len());
+    assert_eq!(25, ids.len());
+    assert!(trades.windows(2).all(|t| t[0].time >= t[1].time));
+}
+
+#[tokio::test]
+async fn pagination_fails_on_full_page_at_one_time() {
+    let server = server().await;
+    let client = server.client_builder().build().unwrap();
+    let time = Utc.timestamp_millis_opt(1_600_000_000_500).unwrap();
+    for _ in 0..12 {
+        server.add_trade("BTC/USD", OrderSide::Buy, dec("50000"), dec("1"), time);
+    }
+
+    let result: Result<Vec<_>, _> = client
+        .paginate(request::Trades::new("BTC/USD").with_limit(5))
+        .try_collect()
+        .await;
+    match result {
+        Err(FtxError::Pagination(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
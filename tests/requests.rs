@@ -0,0 +1,257 @@
+mod common;
+
+use common::{client, dec, market_order, server};
+use ftx_rs::{
+    model::{Order, OrderSide},
+    request::{self, PlaceOrderTypeInfo, Request},
+    FtxError, Interceptor, Metrics, RequestInfo, RetryPolicy,
+};
+use reqwest::{Method, StatusCode};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[tokio::test]
+async fn subaccount_requests() {
+    let server = server().await;
+    server.add_subaccount("key", "my bot");
+    server.set_balance("key", Some("my bot"), "USD", dec("60000"));
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .subaccount(Some("my bot".into()))
+        .build()
+        .unwrap();
+
+    client.request(market_order("1")).await.unwrap();
+    assert_eq!(dec("10000"), server.balance("key", Some("my bot"), "USD"));
+    assert_eq!(dec("100000"), server.balance("key", None, "USD"));
+
+    client.request_as(None, market_order("1")).await.unwrap();
+    assert_eq!(dec("50000"), server.balance("key", None, "USD"));
+    let main = client.with_subaccount(None).unwrap();
+    assert!(main.request(market_order("1.5")).await.is_err());
+}
+
+#[tokio::test]
+async fn batch_requests() {
+    let server = server().await;
+    let nicknames = ["bot 0", "bot 1", "missing", "bot 3"];
+    for (i, nickname) in nicknames.iter().enumerate() {
+        if *nickname != "missing" {
+            server.add_subaccount("key", nickname);
+            server.set_balance("key", Some(nickname), "USD", Decimal::from(i));
+        }
+    }
+    let client = client(&server);
+
+    let results = client
+        .batch(
+            nicknames
+                .iter()
+                .map(|nickname| request::SubaccountBalances { nickname }),
+            2,
+        )
+        .await;
+    assert_eq!(4, results.len());
+    for (i, result) in results.into_iter().enumerate() {
+        if i == 2 {
+            assert!(result.is_err());
+        } else {
+            assert_eq!(Decimal::from(i), result.unwrap()[0].total);
+        }
+    }
+}
+
+#[tokio::test]
+async fn awkward_client_ids() {
+    let server = server().await;
+    let client = client(&server);
+
+    let client_id = "grid/1?level=2#a b";
+    let order = request::PlaceOrder {
+        type_: PlaceOrderTypeInfo::Limit {
+            price: dec("40000"),
+        },
+        client_id: Some(client_id),
+        ..market_order("0.1")
+    };
+    let placed = client.request(order).await.unwrap();
+    let status = client
+        .request(request::OrderStatus {
+            order_request_id: request::OrderRequestId::Client(client_id),
+        })
+        .await
+        .unwrap();
+    assert_eq!(placed.id, status.id);
+}
+
+#[tokio::test]
+async fn invalid_requests_are_not_sent() {
+    let server = server().await;
+    let metrics = Metrics::new();
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    let order = request::PlaceOrder::limit("BTC/USD", OrderSide::Buy, dec("40000"), dec("1"))
+        .post_only()
+        .ioc();
+    match client.request(order).await {
+        Err(FtxError::InvalidRequest(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    assert!(metrics.snapshot().endpoints.is_empty());
+}
+
+#[tokio::test]
+async fn response_metadata() {
+    let server = server().await;
+    let client = server.client_builder().build().unwrap();
+
+    let resp = client.request_with_meta(request::Markets).await.unwrap();
+    assert_eq!(StatusCode::OK, resp.status);
+    assert_eq!(1, resp.result.len());
+    assert_eq!(None, resp.has_more_data);
+    assert!(resp.raw_body.contains("BTC/USD"));
+}
+
+#[tokio::test]
+async fn raw_requests() {
+    let server = server().await;
+    let client = client(&server);
+
+    let market: Value = client
+        .raw_request(Method::GET, "/markets/BTC/USD", &[], None, false)
+        .await
+        .unwrap();
+    assert_eq!("BTC/USD", market["name"]);
+
+    let body =
+        json!({ "market": "BTC/USD", "side": "buy", "type": "market", "price": null, "size": 0.5 });
+    let order: Order = client
+        .raw_request(Method::POST, "/orders", &[], Some(&body), true)
+        .await
+        .unwrap();
+    assert_eq!(dec("0.5"), order.filled_size);
+
+    let e = client
+        .raw_request::<Value>(Method::GET, "/wallet/balances", &[], None, false)
+        .await
+        .unwrap_err();
+    assert_eq!(Some(StatusCode::UNAUTHORIZED), e.status());
+}
+
+#[tokio::test]
+async fn retries_resting_orders_with_client_id() {
+    let server = server().await;
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .retry_policy(
+            RetryPolicy::new()
+                .base_delay(Duration::from_millis(1))
+                .retry_with_client_id(true),
+        )
+        .build()
+        .unwrap();
+
+    server.fail_next(
+        Method::POST,
+        "/orders",
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Service unavailable",
+    );
+    let order = request::PlaceOrder::limit("BTC/USD", OrderSide::Buy, dec("40000"), dec("0.1"))
+        .with_client_id("grid/1");
+    let placed = client.request(order).await.unwrap();
+    assert_eq!(Some("grid/1"), placed.client_id.as_deref());
+    let open = client
+        .request(request::OpenOrders { market: None })
+        .await
+        .unwrap();
+    assert_eq!(1, open.len());
+
+    // a market order is closed once placed, the exchange would accept it twice
+    server.fail_next(
+        Method::POST,
+        "/orders",
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Service unavailable",
+    );
+    let order = request::PlaceOrder {
+        client_id: Some("market/1"),
+        ..market_order("0.1")
+    };
+    let e = client.request(order).await.unwrap_err();
+    assert_eq!(Some(StatusCode::SERVICE_UNAVAILABLE), e.status());
+    assert_eq!(Decimal::ZERO, server.balance("key", None, "BTC"));
+}
+
+/// Request for an endpoint the mock doesn't serve, sent with PUT.
+#[derive(Serialize)]
+struct RenameBot<'a> {
+    nickname: &'a str,
+}
+
+impl Request for RenameBot<'_> {
+    type Response = Value;
+
+    const METHOD: Method = Method::PUT;
+    const NEEDS_AUTH: bool = true;
+
+    fn render_endpoint(&self) -> String {
+        "/bots/rename".into()
+    }
+}
+
+#[derive(Clone, Default)]
+struct Bodies(Arc<Mutex<Vec<String>>>);
+
+impl Interceptor for Bodies {
+    fn on_request(&self, request: &RequestInfo) {
+        let body = request.body.unwrap_or("-").to_owned();
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", request.method, body));
+    }
+}
+
+#[tokio::test]
+async fn put_requests_are_signed_with_their_body() {
+    let server = server().await;
+    let bodies = Bodies::default();
+    let client = server
+        .client_builder()
+        .auth("key", "secret")
+        .interceptor(bodies.clone())
+        .build()
+        .unwrap();
+
+    // the signature covers method, path and body, so it is checked before routing
+    let e = client
+        .request(RenameBot { nickname: "bot" })
+        .await
+        .unwrap_err();
+    assert_eq!(Some(StatusCode::NOT_FOUND), e.status());
+    assert_eq!(vec![r#"PUT {"nickname":"bot"}"#], *bodies.0.lock().unwrap());
+
+    let client = server
+        .client_builder()
+        .auth("key", "wrong")
+        .build()
+        .unwrap();
+    let e = client
+        .request(RenameBot { nickname: "bot" })
+        .await
+        .unwrap_err();
+    assert_eq!(Some(StatusCode::UNAUTHORIZED), e.status());
+}
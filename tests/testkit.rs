@@ -0,0 +1,95 @@
+//! Behaviour of the mock exchange itself.
+
+mod common;
+
+use common::{client, dec, market_order, server};
+use ftx_rs::{
+    model::websocket::{Channel, ChannelData, WsInMessage, WsOutMessage},
+    request, FtxError, RetryPolicy,
+};
+use futures::{SinkExt, StreamExt};
+use reqwest::{Method, StatusCode};
+
+#[tokio::test]
+async fn orders_update_balances() {
+    let server = server().await;
+    let client = client(&server);
+
+    let order = client.request(market_order("1.5")).await.unwrap();
+    assert_eq!(dec("1.5"), order.filled_size);
+    assert_eq!(dec("25000"), server.balance("key", None, "USD"));
+
+    let balances = client.request(request::Balances).await.unwrap();
+    assert_eq!(2, balances.len());
+
+    match client.request(market_order("1")).await {
+        Err(FtxError::Api { error, .. }) => assert_eq!("Not enough balances", error),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn rejects_bad_signatures() {
+    let server = server().await;
+    let client = server
+        .client_builder()
+        .auth("key", "wrong")
+        .build()
+        .unwrap();
+
+    match client.request(request::Balances).await {
+        Err(FtxError::Api { status, error }) => {
+            assert_eq!(StatusCode::UNAUTHORIZED, status);
+            assert_eq!("Not logged in: Invalid signature", error);
+        }
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn injected_errors() {
+    let server = server().await;
+    let client = server
+        .client_builder()
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+
+    server.fail_next(
+        Method::GET,
+        "/markets",
+        StatusCode::TOO_MANY_REQUESTS,
+        "Please retry request",
+    );
+    let e = client.request(request::Markets).await.unwrap_err();
+    assert!(e.is_rate_limit());
+    assert_eq!(1, client.request(request::Markets).await.unwrap().len());
+}
+
+#[tokio::test]
+async fn websocket_order_updates() {
+    let server = server().await;
+    let client = client(&server);
+
+    let mut ws = client.websocket().await.unwrap();
+    client.send_ws_auth_msg(&mut ws).await.unwrap();
+    ws.send(WsOutMessage::Subscribe {
+        channel: Channel::Orders,
+    })
+    .await
+    .unwrap();
+    match ws.next().await.unwrap().unwrap() {
+        WsInMessage::Subscribed {
+            channel: Channel::Orders,
+        } => {}
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    let order = client.request(market_order("0.1")).await.unwrap();
+    match ws.next().await.unwrap().unwrap() {
+        WsInMessage::Update {
+            data: ChannelData::Orders { data },
+        } => assert_eq!(order.id, data.id),
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}